@@ -0,0 +1,168 @@
+use crate::{FusionAxesAlignment, FusionFloat, FusionVector};
+
+/// Swaps sensor axes for alignment with the body axes.
+///
+/// The alignment names the sensor axes that correspond to the body X, Y and Z axes,
+/// e.g. `PYNXPZ` (+Y-X+Z) maps sensor +Y to body X, sensor -X to body Y and sensor +Z to body Z.
+pub fn swap<T: FusionFloat>(sensor: FusionVector<T>, alignment: FusionAxesAlignment) -> FusionVector<T> {
+    match alignment {
+        FusionAxesAlignment::PXPYPZ => sensor,
+        FusionAxesAlignment::PXNZPY => FusionVector {
+            x: sensor.x,
+            y: -sensor.z,
+            z: sensor.y,
+        },
+        FusionAxesAlignment::PXNYNZ => FusionVector {
+            x: sensor.x,
+            y: -sensor.y,
+            z: -sensor.z,
+        },
+        FusionAxesAlignment::PXPZNY => FusionVector {
+            x: sensor.x,
+            y: sensor.z,
+            z: -sensor.y,
+        },
+        FusionAxesAlignment::NXPYNZ => FusionVector {
+            x: -sensor.x,
+            y: sensor.y,
+            z: -sensor.z,
+        },
+        FusionAxesAlignment::NXPZPY => FusionVector {
+            x: -sensor.x,
+            y: sensor.z,
+            z: sensor.y,
+        },
+        FusionAxesAlignment::NXNYPZ => FusionVector {
+            x: -sensor.x,
+            y: -sensor.y,
+            z: sensor.z,
+        },
+        FusionAxesAlignment::NXNZNY => FusionVector {
+            x: -sensor.x,
+            y: -sensor.z,
+            z: -sensor.y,
+        },
+        FusionAxesAlignment::PYNXPZ => FusionVector {
+            x: sensor.y,
+            y: -sensor.x,
+            z: sensor.z,
+        },
+        FusionAxesAlignment::PYNZNX => FusionVector {
+            x: sensor.y,
+            y: -sensor.z,
+            z: -sensor.x,
+        },
+        FusionAxesAlignment::PYPXNZ => FusionVector {
+            x: sensor.y,
+            y: sensor.x,
+            z: -sensor.z,
+        },
+        FusionAxesAlignment::PYPZPX => FusionVector {
+            x: sensor.y,
+            y: sensor.z,
+            z: sensor.x,
+        },
+        FusionAxesAlignment::NYPXPZ => FusionVector {
+            x: -sensor.y,
+            y: sensor.x,
+            z: sensor.z,
+        },
+        FusionAxesAlignment::NYNZPX => FusionVector {
+            x: -sensor.y,
+            y: -sensor.z,
+            z: sensor.x,
+        },
+        FusionAxesAlignment::NYNXNZ => FusionVector {
+            x: -sensor.y,
+            y: -sensor.x,
+            z: -sensor.z,
+        },
+        FusionAxesAlignment::NYPZNX => FusionVector {
+            x: -sensor.y,
+            y: sensor.z,
+            z: -sensor.x,
+        },
+        FusionAxesAlignment::PZPYNX => FusionVector {
+            x: sensor.z,
+            y: sensor.y,
+            z: -sensor.x,
+        },
+        FusionAxesAlignment::PZPXPY => FusionVector {
+            x: sensor.z,
+            y: sensor.x,
+            z: sensor.y,
+        },
+        FusionAxesAlignment::PZNYPX => FusionVector {
+            x: sensor.z,
+            y: -sensor.y,
+            z: sensor.x,
+        },
+        FusionAxesAlignment::PZNXNY => FusionVector {
+            x: sensor.z,
+            y: -sensor.x,
+            z: -sensor.y,
+        },
+        FusionAxesAlignment::NZPYPX => FusionVector {
+            x: -sensor.z,
+            y: sensor.y,
+            z: sensor.x,
+        },
+        FusionAxesAlignment::NZNXPY => FusionVector {
+            x: -sensor.z,
+            y: -sensor.x,
+            z: sensor.y,
+        },
+        FusionAxesAlignment::NZNYNX => FusionVector {
+            x: -sensor.z,
+            y: -sensor.y,
+            z: -sensor.x,
+        },
+        FusionAxesAlignment::NZPXNY => FusionVector {
+            x: -sensor.z,
+            y: sensor.x,
+            z: -sensor.y,
+        },
+    }
+}
+
+#[test]
+fn swap_test() {
+    // Expected results of FusionAxesSwap for sensor (1, 2, 3)
+    let cases = [
+        (FusionAxesAlignment::PXPYPZ, [1.0f32, 2.0f32, 3.0f32]),
+        (FusionAxesAlignment::PXNZPY, [1.0f32, -3.0f32, 2.0f32]),
+        (FusionAxesAlignment::PXNYNZ, [1.0f32, -2.0f32, -3.0f32]),
+        (FusionAxesAlignment::PXPZNY, [1.0f32, 3.0f32, -2.0f32]),
+        (FusionAxesAlignment::NXPYNZ, [-1.0f32, 2.0f32, -3.0f32]),
+        (FusionAxesAlignment::NXPZPY, [-1.0f32, 3.0f32, 2.0f32]),
+        (FusionAxesAlignment::NXNYPZ, [-1.0f32, -2.0f32, 3.0f32]),
+        (FusionAxesAlignment::NXNZNY, [-1.0f32, -3.0f32, -2.0f32]),
+        (FusionAxesAlignment::PYNXPZ, [2.0f32, -1.0f32, 3.0f32]),
+        (FusionAxesAlignment::PYNZNX, [2.0f32, -3.0f32, -1.0f32]),
+        (FusionAxesAlignment::PYPXNZ, [2.0f32, 1.0f32, -3.0f32]),
+        (FusionAxesAlignment::PYPZPX, [2.0f32, 3.0f32, 1.0f32]),
+        (FusionAxesAlignment::NYPXPZ, [-2.0f32, 1.0f32, 3.0f32]),
+        (FusionAxesAlignment::NYNZPX, [-2.0f32, -3.0f32, 1.0f32]),
+        (FusionAxesAlignment::NYNXNZ, [-2.0f32, -1.0f32, -3.0f32]),
+        (FusionAxesAlignment::NYPZNX, [-2.0f32, 3.0f32, -1.0f32]),
+        (FusionAxesAlignment::PZPYNX, [3.0f32, 2.0f32, -1.0f32]),
+        (FusionAxesAlignment::PZPXPY, [3.0f32, 1.0f32, 2.0f32]),
+        (FusionAxesAlignment::PZNYPX, [3.0f32, -2.0f32, 1.0f32]),
+        (FusionAxesAlignment::PZNXNY, [3.0f32, -1.0f32, -2.0f32]),
+        (FusionAxesAlignment::NZPYPX, [-3.0f32, 2.0f32, 1.0f32]),
+        (FusionAxesAlignment::NZNXPY, [-3.0f32, -1.0f32, 2.0f32]),
+        (FusionAxesAlignment::NZNYNX, [-3.0f32, -2.0f32, -1.0f32]),
+        (FusionAxesAlignment::NZPXNY, [-3.0f32, 1.0f32, -2.0f32]),
+    ];
+    let sensor = FusionVector::new(1.0f32, 2.0f32, 3.0f32);
+    for (alignment, expected) in cases {
+        let result = swap(sensor, alignment);
+        assert_eq!([result.x, result.y, result.z], expected);
+        // Every alignment is a proper rotation, so swapped axes remain right-handed
+        let x = swap(FusionVector::new(1.0f32, 0.0f32, 0.0f32), alignment);
+        let y = swap(FusionVector::new(0.0f32, 1.0f32, 0.0f32), alignment);
+        let z = swap(FusionVector::new(0.0f32, 0.0f32, 1.0f32), alignment);
+        let cross = x.cross_product(&y);
+        assert_eq!([cross.x, cross.y, cross.z], [z.x, z.y, z.z]);
+    }
+}
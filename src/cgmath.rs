@@ -0,0 +1,88 @@
+//! Conversions between the Fusion math types and cgmath.
+//!
+//! Both use Hamilton quaternions, `cgmath::Quaternion` has the scalar part `s` and the vector part `v`, so
+//! `Quaternion::from(fusion.quaternion()).rotate_vector(v)` is the same as `FusionQuaternion::rotate_vector`.
+//! cgmath matrices are stored by column, `Matrix3::from(m).x` is the first column `(m.xx, m.yx, m.zx)`.
+use crate::{FusionFloat, FusionMatrix, FusionQuaternion, FusionVector};
+use cgmath::{Matrix3, Point3, Quaternion, Vector3};
+
+impl<T: FusionFloat> From<Vector3<T>> for FusionVector<T> {
+    fn from(v: Vector3<T>) -> Self {
+        Self::new(v.x, v.y, v.z)
+    }
+}
+
+impl<T: FusionFloat> From<FusionVector<T>> for Vector3<T> {
+    fn from(v: FusionVector<T>) -> Self {
+        Vector3 { x: v.x, y: v.y, z: v.z }
+    }
+}
+
+impl<T: FusionFloat> From<Point3<T>> for FusionVector<T> {
+    fn from(p: Point3<T>) -> Self {
+        Self::new(p.x, p.y, p.z)
+    }
+}
+
+impl<T: FusionFloat> From<FusionVector<T>> for Point3<T> {
+    fn from(v: FusionVector<T>) -> Self {
+        Point3 { x: v.x, y: v.y, z: v.z }
+    }
+}
+
+impl<T: FusionFloat> From<Quaternion<T>> for FusionQuaternion<T> {
+    fn from(q: Quaternion<T>) -> Self {
+        Self {
+            w: q.s,
+            x: q.v.x,
+            y: q.v.y,
+            z: q.v.z,
+        }
+    }
+}
+
+impl<T: FusionFloat> From<FusionQuaternion<T>> for Quaternion<T> {
+    fn from(q: FusionQuaternion<T>) -> Self {
+        Quaternion { s: q.w, v: Vector3 { x: q.x, y: q.y, z: q.z } }
+    }
+}
+
+impl<T: FusionFloat> From<Matrix3<T>> for FusionMatrix<T> {
+    fn from(m: Matrix3<T>) -> Self {
+        Self::new(m.x.x, m.y.x, m.z.x, m.x.y, m.y.y, m.z.y, m.x.z, m.y.z, m.z.z)
+    }
+}
+
+impl<T: FusionFloat> From<FusionMatrix<T>> for Matrix3<T> {
+    fn from(m: FusionMatrix<T>) -> Self {
+        Matrix3 {
+            x: Vector3 { x: m.xx, y: m.yx, z: m.zx },
+            y: Vector3 { x: m.xy, y: m.yy, z: m.zy },
+            z: Vector3 { x: m.xz, y: m.yz, z: m.zz },
+        }
+    }
+}
+
+#[cfg(feature = "libm")]
+#[test]
+fn conversion_test() {
+    use cgmath::Rotation;
+    let q = FusionQuaternion::from_axis_angle(FusionVector::new(1.0f64, -2.0f64, 0.5f64), 0.8f64);
+    let v = FusionVector::new(0.3f64, 0.2f64, -1.0f64);
+    let m = FusionMatrix::from([[1.0f64, 2.0f64, 3.0f64], [4.0f64, 5.0f64, 6.0f64], [7.0f64, 8.0f64, 9.0f64]]);
+
+    // Exact round trips
+    assert_eq!(FusionQuaternion::from(Quaternion::from(q)), q);
+    assert_eq!(FusionVector::from(Vector3::from(v)), v);
+    assert_eq!(FusionVector::from(Point3::from(v)), v);
+    assert_eq!(FusionMatrix::from(Matrix3::from(m)), m);
+
+    // Row and column order
+    assert_eq!(Matrix3::from(m).x, Vector3::new(1.0f64, 4.0f64, 7.0f64));
+    assert_eq!(Matrix3::from(m) * Vector3::new(1.0f64, 0.0f64, 0.0f64), Vector3::new(1.0f64, 4.0f64, 7.0f64));
+
+    // Same rotation
+    let expected = q.rotate_vector(v);
+    assert!(FusionVector::from(Quaternion::from(q).rotate_vector(v.into())).approx_eq(&expected, 1e-12f64));
+    assert!(FusionMatrix::from(Matrix3::from(Quaternion::from(q))).approx_eq(&q.rotation(), 1e-12f64));
+}
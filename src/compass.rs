@@ -0,0 +1,52 @@
+use crate::{fusion_radians_to_degrees, FusionConvention, FusionFloat, FusionVector};
+
+/// Calculates the tilt-compensated magnetic heading in degrees from accelerometer
+/// and magnetometer measurements.
+///
+/// # Examples
+///
+/// ```
+/// use imu_fusion::compass::fusion_compass_calculate_heading;
+/// use imu_fusion::{FusionConvention, FusionVector};
+///
+/// let acc = FusionVector::new(0f32, 0f32, 1.0f32); // replace this with actual accelerometer data in g
+/// let mag = FusionVector::new(1.0f32, 0f32, 0f32); // replace this with actual magnetometer data in arbitrary units
+/// let heading = fusion_compass_calculate_heading(FusionConvention::NWU, acc, mag);
+/// println!("Heading {}", heading);
+/// ```
+pub fn fusion_compass_calculate_heading<T: FusionFloat>(convention: FusionConvention, acc: FusionVector<T>, mag: FusionVector<T>) -> T {
+    match convention {
+        FusionConvention::NWU => {
+            let west = acc.cross_product(&mag).normalize();
+            let north = west.cross_product(&acc).normalize();
+            fusion_radians_to_degrees(west.x.atan2(north.x))
+        }
+        FusionConvention::ENU => {
+            let west = acc.cross_product(&mag).normalize();
+            let north = west.cross_product(&acc).normalize();
+            let east = west * -T::ONE;
+            fusion_radians_to_degrees(north.x.atan2(east.x))
+        }
+        FusionConvention::NED => {
+            let up = acc * -T::ONE;
+            let west = up.cross_product(&mag).normalize();
+            let north = west.cross_product(&up).normalize();
+            fusion_radians_to_degrees(west.x.atan2(north.x))
+        }
+    }
+}
+
+#[test]
+fn fusion_compass_calculate_heading_test() {
+    let cases = [
+        (FusionConvention::NWU, FusionVector::new(0.0f32, 0.0f32, 1.0f32), FusionVector::new(1.0f32, 0.0f32, -0.5f32), 0.0f32),
+        (FusionConvention::NWU, FusionVector::new(0.0f32, 0.0f32, 1.0f32), FusionVector::new(0.0f32, -1.0f32, -0.5f32), 90.0f32),
+        (FusionConvention::ENU, FusionVector::new(0.0f32, 0.0f32, 1.0f32), FusionVector::new(1.0f32, 0.0f32, -0.5f32), 90.0f32),
+        (FusionConvention::ENU, FusionVector::new(0.0f32, 0.0f32, 1.0f32), FusionVector::new(0.0f32, 1.0f32, -0.5f32), 0.0f32),
+        (FusionConvention::NED, FusionVector::new(0.0f32, 0.0f32, -1.0f32), FusionVector::new(1.0f32, 0.0f32, 0.5f32), 0.0f32),
+        (FusionConvention::NED, FusionVector::new(0.0f32, 0.0f32, -1.0f32), FusionVector::new(0.0f32, 1.0f32, 0.5f32), -90.0f32),
+    ];
+    for (convention, acc, mag, expected) in cases {
+        assert!((fusion_compass_calculate_heading(convention, acc, mag) - expected).abs() < 0.1f32);
+    }
+}
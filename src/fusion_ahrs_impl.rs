@@ -1,107 +1,130 @@
-use libm::{atan2f, cosf, fabsf, sinf};
-use crate::{fusion_degrees_to_radians, FusionAhrs, FusionAhrsFlags, FusionAhrsSettings, FusionConvention, FusionQuaternion, FusionVector};
+use crate::compass::fusion_compass_calculate_heading;
+use crate::{Angle, asin_safe, fusion_degrees_to_radians, fusion_radians_to_degrees, FusionAhrs, FusionAhrsFlags, FusionAhrsInternalStates, FusionAhrsSettings, FusionConvention, FusionError, FusionEuler, FusionFloat, FusionQuaternion, FusionVector, OrientationFilter};
 use crate::FusionConvention::NWU;
 
 /**
  * Initial gain used during the initialisation.
  */
-const INITIAL_GAIN: f32 = 10.0f32;
+const INITIAL_GAIN: f64 = 10.0f64;
 /**
  * Initialisation period in seconds.
  */
-const INITIALISATION_PERIOD: f32 = 3.0f32;
+const INITIALISATION_PERIOD: f64 = 3.0f64;
 
 impl FusionAhrs {
+    /// Creates the AHRS algorithm with default settings, use `FusionAhrs::default()` for other precisions.
     pub fn new() -> Self {
-        let settings = FusionAhrsSettings::new();
-        let gain = settings.gain;
-        let recovery_trigger_period = settings.recovery_trigger_period;
-        Self {
-            settings,
-            quaternion: FusionQuaternion::identity(),
-            acc: FusionVector::zero(),
-            initialising: true,
-            ramped_gain: INITIAL_GAIN,
-            ramped_gain_step: (INITIAL_GAIN - gain) / INITIALISATION_PERIOD,
-            angular_rate_recovery: false,
-            half_accelerometer_feedback: FusionVector::zero(),
-            half_magnetometer_feedback: FusionVector::zero(),
-            accelerometer_ignored: false,
-            acceleration_recovery_trigger: 0,
-            acceleration_recovery_timeout: recovery_trigger_period,
-            magnetometer_ignored: false,
-            magnetic_recovery_trigger: 0,
-            magnetic_recovery_timeout: recovery_trigger_period,
-        }
+        Self::default()
     }
+}
 
-    pub fn update_settings(&mut self, settings: FusionAhrsSettings) {
+impl<T: FusionFloat> FusionAhrs<T> {
+    pub fn update_settings(&mut self, settings: FusionAhrsSettings<T>) {
         self.settings.convention = settings.convention;
         self.settings.gain = settings.gain;
-        self.settings.gyr_range = if settings.gyr_range == 0.0f32 { f32::MAX } else { 0.98f32 * settings.gyr_range };
-        self.settings.acc_rejection = if settings.acc_rejection == 0.0f32 {
-            f32::MAX
+        self.settings.gyr_range = if settings.gyr_range == T::ZERO { T::MAX } else { T::from_f64(0.98f64) * settings.gyr_range };
+        self.settings.acc_rejection = if settings.acc_rejection == T::ZERO {
+            T::MAX
         } else {
-            let v = 0.5f32 * sinf(fusion_degrees_to_radians(settings.acc_rejection));
+            let v = T::HALF * fusion_degrees_to_radians(settings.acc_rejection).sin();
             v * v
         };
-        self.settings.mag_rejection = if settings.mag_rejection == 0.0f32 {
-            f32::MAX
+        self.settings.mag_rejection = if settings.mag_rejection == T::ZERO {
+            T::MAX
         } else {
-            let v = 0.5f32 * sinf(fusion_degrees_to_radians(settings.mag_rejection));
+            let v = T::HALF * fusion_degrees_to_radians(settings.mag_rejection).sin();
             v * v
         };
         self.settings.recovery_trigger_period = settings.recovery_trigger_period;
         self.acceleration_recovery_timeout = self.settings.recovery_trigger_period;
         self.magnetic_recovery_timeout = self.settings.recovery_trigger_period;
-        if settings.gain == 0.0f32 || settings.recovery_trigger_period == 0 { // disable acceleration and magnetic rejection features if gain is zero
-            self.settings.acc_rejection = f32::MAX;
-            self.settings.mag_rejection = f32::MAX;
+        if settings.gain == T::ZERO || settings.recovery_trigger_period == 0 { // disable acceleration and magnetic rejection features if gain is zero
+            self.settings.acc_rejection = T::MAX;
+            self.settings.mag_rejection = T::MAX;
         }
         if !self.initialising {
             self.ramped_gain = settings.gain;
         }
-        self.ramped_gain_step = (INITIAL_GAIN - self.settings.gain) / INITIALISATION_PERIOD;
+        self.ramped_gain_step = (T::from_f64(INITIAL_GAIN) - self.settings.gain) / T::from_f64(INITIALISATION_PERIOD);
     }
 
-    pub fn update_no_mag(&mut self, gyr: FusionVector, acc: FusionVector, dt: f32) {
+    pub fn update_no_mag(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, dt: T) {
         self.update(gyr, acc, FusionVector::zero(), dt);
         // Zero heading during initialisation
-        if self.initialising == true {
-            self.set_heading(0.0f32);
+        if self.initialising {
+            self.set_heading(T::ZERO);
         }
     }
 
-    pub fn update_external_heading(&mut self, gyr: FusionVector, acc: FusionVector, heading: f32, dt: f32) {
+    pub fn update_external_heading(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, heading: T, dt: T) {
         let q = self.quaternion;
         // Calculate roll
-        let roll = atan2f(q.w * q.x + q.y * q.z, 0.5f32 - q.y * q.y - q.x * q.x);
+        let roll = (q.w * q.x + q.y * q.z).atan2(T::HALF - q.y * q.y - q.x * q.x);
         // Calculate magnetometer// Calculate magnetometer
         let heading_radians = fusion_degrees_to_radians(heading);
-        let sin_heading_radians = sinf(heading_radians);
+        let sin_heading_radians = heading_radians.sin();
         let magnetometer = FusionVector {
-            x: cosf(heading_radians),
-            y: -1.0f32 * cosf(roll) * sin_heading_radians,
-            z: sin_heading_radians * sinf(roll),
+            x: heading_radians.cos(),
+            y: -roll.cos() * sin_heading_radians,
+            z: sin_heading_radians * roll.sin(),
         };
         // Update AHRS algorithm
         self.update(gyr, acc, magnetometer, dt);
     }
 
-    pub fn update(&mut self, gyr: FusionVector, acc: FusionVector, mag: FusionVector, dt: f32) {
+    /// Same as `update_no_mag`, but rejects non-finite inputs and restores the previous state if the
+    /// quaternion becomes non-finite.
+    pub fn try_update_no_mag(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, dt: T) -> Result<(), FusionError> {
+        check_inputs(gyr, acc, FusionVector::zero(), dt)?;
+        let previous = self.clone();
+        self.update_no_mag(gyr, acc, dt);
+        self.check_quaternion(previous)
+    }
+
+    /// Same as `update_external_heading`, but rejects non-finite inputs and restores the previous state if the
+    /// quaternion becomes non-finite.
+    pub fn try_update_external_heading(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, heading: T, dt: T) -> Result<(), FusionError> {
+        check_inputs(gyr, acc, FusionVector::zero(), dt)?;
+        if !heading.is_finite() {
+            return Err(FusionError::NonFiniteHeading);
+        }
+        let previous = self.clone();
+        self.update_external_heading(gyr, acc, heading, dt);
+        self.check_quaternion(previous)
+    }
+
+    /// Same as `update`, but rejects non-finite inputs and restores the previous state if the
+    /// quaternion becomes non-finite.
+    pub fn try_update(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, mag: FusionVector<T>, dt: T) -> Result<(), FusionError> {
+        check_inputs(gyr, acc, mag, dt)?;
+        let previous = self.clone();
+        self.update(gyr, acc, mag, dt);
+        self.check_quaternion(previous)
+    }
+
+    fn check_quaternion(&mut self, previous: Self) -> Result<(), FusionError> {
+        if self.quaternion.is_finite() {
+            Ok(())
+        } else {
+            *self = previous;
+            Err(FusionError::NonFiniteQuaternion)
+        }
+    }
+
+    pub fn update(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, mag: FusionVector<T>, dt: T) {
         // Store accelerometer
         self.acc = acc;
         // Reinitialise if gyroscope range exceeded
-        if fabsf(gyr.x) > self.settings.gyr_range || fabsf(gyr.y) > self.settings.gyr_range || fabsf(gyr.z) > self.settings.gyr_range {
+        if gyr.x.abs() > self.settings.gyr_range || gyr.y.abs() > self.settings.gyr_range || gyr.z.abs() > self.settings.gyr_range {
             let quaternion = self.quaternion;
             self.reset();
             self.quaternion = quaternion;
             self.angular_rate_recovery = true;
         }
         // Ramp down gain during initialisation
-        if self.initialising == true {
+        if self.initialising {
             self.ramped_gain -= self.ramped_gain_step * dt;
-            if self.ramped_gain < self.settings.gain || self.settings.gain == 0.0f32 {
+            if self.ramped_gain < self.settings.gain || self.settings.gain == T::ZERO {
                 self.ramped_gain = self.settings.gain;
                 self.initialising = false;
                 self.angular_rate_recovery = false;
@@ -115,9 +138,9 @@ impl FusionAhrs {
         self.accelerometer_ignored = true;
         if !self.acc.is_zero() {
             // Calculate accelerometer feedback scaled by 0.5
-            self.half_accelerometer_feedback = self.feedback(self.acc.normalize(), half_gravity);
+            self.half_accelerometer_feedback = feedback(self.acc.normalize(), half_gravity);
             // Don't ignore accelerometer if acceleration error below threshold
-            if self.initialising == true || self.half_accelerometer_feedback.magnitude() <= self.settings.acc_rejection {
+            if self.initialising || self.half_accelerometer_feedback.norm_squared() <= self.settings.acc_rejection {
                 self.accelerometer_ignored = false;
                 self.acceleration_recovery_trigger -= 9;
             } else {
@@ -144,9 +167,9 @@ impl FusionAhrs {
             let half_magnetic = self.calculate_half_magnetic();
             // Calculate magnetometer feedback scaled by 0.5
 
-            self.half_magnetometer_feedback = self.feedback(half_gravity.cross_product(&mag).normalize(), half_magnetic);
+            self.half_magnetometer_feedback = feedback(half_gravity.cross_product(&mag).normalize(), half_magnetic);
             // Don't ignore magnetometer if magnetic error below threshold
-            if self.initialising == true || self.half_magnetometer_feedback.magnitude() <= self.settings.mag_rejection {
+            if self.initialising || self.half_magnetometer_feedback.norm_squared() <= self.settings.mag_rejection {
                 self.magnetometer_ignored = false;
                 self.magnetic_recovery_trigger -= 9;
             } else {
@@ -167,7 +190,7 @@ impl FusionAhrs {
         }
 
         // Convert gyroscope to radians per second scaled by 0.5
-        let half_gyroscope = gyr * fusion_degrees_to_radians(0.5f32);
+        let half_gyroscope = gyr * fusion_degrees_to_radians(T::HALF);
         // Apply feedback to gyroscope
         let adjusted_half_gyroscope = half_gyroscope + (half_accelerometer_feedback + half_magnetometer_feedback) * self.ramped_gain;
         // Integrate rate of change of quaternion
@@ -177,32 +200,77 @@ impl FusionAhrs {
     }
 
 
-    fn feedback(&self, sensor: FusionVector, reference: FusionVector) -> FusionVector {
-        if sensor.dot_product(&reference) < 0.0f32 { // if error is >90 degrees
-            sensor.cross_product(&reference).normalize()
-        } else {
-            sensor.cross_product(&reference)
-        }
-    }
-
-    fn set_heading(&mut self, heading: f32) {
+    /// Sets the heading of the orientation measurement provided by the AHRS algorithm.
+    ///
+    /// The heading is provided in degrees.
+    pub fn set_heading(&mut self, heading: T) {
         let q = self.quaternion;
-        let yaw = atan2f(q.w * q.z + q.x * q.y, 0.5f32 - q.y * q.y - q.z * q.z);
-        let half_yaw_minus_heading = 0.5f32 * (yaw - fusion_degrees_to_radians(heading));
+        let yaw = (q.w * q.z + q.x * q.y).atan2(T::HALF - q.y * q.y - q.z * q.z);
+        let half_yaw_minus_heading = T::HALF * (yaw - fusion_degrees_to_radians(heading));
         let rotation = FusionQuaternion {
-            w: cosf(half_yaw_minus_heading),
-            x: 0.0f32,
-            y: 0.0f32,
-            z: -1.0f32 * sinf(half_yaw_minus_heading),
+            w: half_yaw_minus_heading.cos(),
+            x: T::ZERO,
+            y: T::ZERO,
+            z: -half_yaw_minus_heading.sin(),
         };
         self.quaternion = rotation * self.quaternion;
     }
 
+    /// Sets the orientation of the AHRS algorithm.
+    ///
+    /// The initialisation gain ramp is not affected, use `initialise_from` to skip it.
+    pub fn set_quaternion(&mut self, quaternion: FusionQuaternion<T>) {
+        self.quaternion = quaternion.normalize();
+    }
+
+    /// Sets the orientation of the AHRS algorithm from ZYX euler angles in degrees.
+    ///
+    /// The initialisation gain ramp is not affected, use `initialise_from` to skip it.
+    pub fn set_euler(&mut self, euler: FusionEuler<T>) {
+        self.quaternion = FusionQuaternion::from_euler(euler);
+    }
+
+    /// Initialises the orientation directly from accelerometer data in g and magnetometer data
+    /// in arbitrary units, and skips the initialisation gain ramp.
+    ///
+    /// The heading is set to zero if the magnetometer measurement is zero. If the accelerometer measurement is zero,
+    /// the algorithm is only reset and initialises with the gain ramp as usual.
+    pub fn initialise_from(&mut self, acc: FusionVector<T>, mag: FusionVector<T>) {
+        self.reset();
+        if !acc.is_zero() {
+            // Calculate roll and pitch from direction of gravity
+            let (roll, pitch) = match self.settings.convention {
+                FusionConvention::ENU | FusionConvention::NWU => {
+                    (acc.y.atan2(acc.z), (-acc.x).atan2((acc.y * acc.y + acc.z * acc.z).sqrt()))
+                }
+                FusionConvention::NED => {
+                    ((-acc.y).atan2(-acc.z), acc.x.atan2((acc.y * acc.y + acc.z * acc.z).sqrt()))
+                }
+            };
+            // Calculate heading from magnetic field
+            let yaw = if mag.is_zero() {
+                T::ZERO
+            } else {
+                fusion_compass_calculate_heading(self.settings.convention, acc, mag)
+            };
+            self.set_euler(FusionEuler {
+                angle: Angle {
+                    roll: fusion_radians_to_degrees(roll),
+                    pitch: fusion_radians_to_degrees(pitch),
+                    yaw,
+                }
+            });
+            // Skip gain ramp
+            self.initialising = false;
+            self.ramped_gain = self.settings.gain;
+        }
+    }
+
     pub fn reset(&mut self) {
         self.quaternion = FusionQuaternion::identity();
         self.acc = FusionVector::zero();
         self.initialising = true;
-        self.ramped_gain = INITIAL_GAIN;
+        self.ramped_gain = T::from_f64(INITIAL_GAIN);
         self.angular_rate_recovery = false;
         self.half_accelerometer_feedback = FusionVector::zero();
         self.half_magnetometer_feedback = FusionVector::zero();
@@ -214,56 +282,15 @@ impl FusionAhrs {
         self.magnetic_recovery_timeout = self.settings.recovery_trigger_period;
     }
 
-    pub fn calculate_half_gravity(&self) -> FusionVector {
-        let q = self.quaternion;
-        match self.settings.convention {
-            FusionConvention::ENU | FusionConvention::NWU => {
-                FusionVector {
-                    x: q.x * q.z - q.w * q.y,
-                    y: q.y * q.z + q.w * q.x,
-                    z: q.w * q.w - 0.5f32 + q.z * q.z,
-                }
-            }
-            FusionConvention::NED => {
-                FusionVector {
-                    x: q.w * q.y - q.x * q.z,
-                    y: -1.0f32 * (q.y * q.z + q.w * q.x),
-                    z: 0.5f32 - q.w * q.w - q.z * q.z,
-                }
-            }
-        }
+    pub fn calculate_half_gravity(&self) -> FusionVector<T> {
+        half_gravity(self.quaternion, self.settings.convention)
     }
 
-    pub fn calculate_half_magnetic(&self) -> FusionVector {
-        let q = self.quaternion;
-        match self.settings.convention {
-            FusionConvention::NWU => {
-                let half_magnetic = FusionVector {
-                    x: q.x * q.y + q.w * q.z,
-                    y: q.w * q.w - 0.5f32 + q.y * q.y,
-                    z: q.y * q.z - q.w * q.x,
-                };
-                half_magnetic
-            }
-            FusionConvention::ENU => {
-                let half_magnetic = FusionVector {
-                    x: 0.5f32 - q.w * q.w - q.x * q.x,
-                    y: q.w * q.z - q.x * q.y,
-                    z: -1.0f32 * (q.x * q.z + q.w * q.y),
-                };
-                half_magnetic
-            }
-            FusionConvention::NED => {
-                let half_magnetic = FusionVector {
-                    x: -1.0f32 * (q.x * q.y + q.w * q.z),
-                    y: 0.5f32 - q.w * q.w - q.y * q.y,
-                    z: q.w * q.x - q.y * q.z,
-                };
-                half_magnetic
-            }
-        }
+    pub fn calculate_half_magnetic(&self) -> FusionVector<T> {
+        half_magnetic(self.quaternion, self.settings.convention)
     }
-    pub fn earth_acc(&self) -> FusionVector {
+
+    pub fn earth_acc(&self) -> FusionVector<T> {
         // Calculate accelerometer measurement in the Earth coordinate frame
         let q = self.quaternion;
         let a = self.acc;
@@ -275,31 +302,31 @@ impl FusionAhrs {
         let qxqz = q.x * q.z;
         let qyqz = q.y * q.z;
         let mut accelerometer = FusionVector {
-            x: 2.0f32 * ((qwqw - 0.5f32 + q.x * q.x) * a.x + (qxqy - qwqz) * a.y + (qxqz + qwqy) * a.z),
-            y: 2.0f32 * ((qxqy + qwqz) * a.x + (qwqw - 0.5f32 + q.y * q.y) * a.y + (qyqz - qwqx) * a.z),
-            z: 2.0f32 * ((qxqz - qwqy) * a.x + (qyqz + qwqx) * a.y + (qwqw - 0.5f32 + q.z * q.z) * a.z),
+            x: T::TWO * ((qwqw - T::HALF + q.x * q.x) * a.x + (qxqy - qwqz) * a.y + (qxqz + qwqy) * a.z),
+            y: T::TWO * ((qxqy + qwqz) * a.x + (qwqw - T::HALF + q.y * q.y) * a.y + (qyqz - qwqx) * a.z),
+            z: T::TWO * ((qxqz - qwqy) * a.x + (qyqz + qwqx) * a.y + (qwqw - T::HALF + q.z * q.z) * a.z),
         };
         // Remove gravity from accelerometer measurement
         match self.settings.convention {
             FusionConvention::ENU | FusionConvention::NWU => {
-                accelerometer.z -= 1.0f32;
+                accelerometer.z -= T::ONE;
                 accelerometer
             }
             FusionConvention::NED => {
-                accelerometer.z += 1.0f32;
+                accelerometer.z += T::ONE;
                 accelerometer
             }
         }
     }
 
-    pub fn linear_acc(&self) -> FusionVector {
+    pub fn linear_acc(&self) -> FusionVector<T> {
         // Calculate accelerometer measurement in the Earth coordinate frame
         let q = self.quaternion;
         // Calculate gravity in the sensor coordinate frame
         let gravity = FusionVector {
-            x: 2.0f32 * (q.x * q.z - q.w * q.y),
-            y: 2.0f32 * (q.y * q.z + q.w * q.x),
-            z: 2.0f32 * (q.w * q.w - 0.5f32 + q.z * q.z),
+            x: T::TWO * (q.x * q.z - q.w * q.y),
+            y: T::TWO * (q.y * q.z + q.w * q.x),
+            z: T::TWO * (q.w * q.w - T::HALF + q.z * q.z),
         };
 
         // Remove gravity from accelerometer measurement
@@ -313,6 +340,12 @@ impl FusionAhrs {
         }
     }
 
+    /// Returns the direction of gravity in the sensor coordinate frame, in g, as it would be measured
+    /// by a stationary accelerometer.
+    pub fn gravity(&self) -> FusionVector<T> {
+        self.calculate_half_gravity() * T::TWO
+    }
+
     pub fn flags(&self) -> FusionAhrsFlags {
         FusionAhrsFlags {
             initializing: self.initialising,
@@ -322,21 +355,167 @@ impl FusionAhrs {
             magnetic_recovery: self.magnetic_recovery_trigger > self.magnetic_recovery_timeout,
         }
     }
+
+    pub fn internal_states(&self) -> FusionAhrsInternalStates<T> {
+        let recovery_trigger_ratio = |trigger: i32| {
+            if self.settings.recovery_trigger_period == 0 {
+                T::ZERO
+            } else {
+                T::from_f64(trigger as f64) / T::from_f64(self.settings.recovery_trigger_period as f64)
+            }
+        };
+        FusionAhrsInternalStates {
+            acceleration_error: fusion_radians_to_degrees(asin_safe(T::TWO * self.half_accelerometer_feedback.norm_squared().sqrt())),
+            accelerometer_ignored: self.accelerometer_ignored,
+            acceleration_recovery_trigger: recovery_trigger_ratio(self.acceleration_recovery_trigger),
+            magnetic_error: fusion_radians_to_degrees(asin_safe(T::TWO * self.half_magnetometer_feedback.norm_squared().sqrt())),
+            magnetometer_ignored: self.magnetometer_ignored,
+            magnetic_recovery_trigger: recovery_trigger_ratio(self.magnetic_recovery_trigger),
+        }
+    }
+}
+
+impl<T: FusionFloat> OrientationFilter<T> for FusionAhrs<T> {
+    fn update(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, mag: Option<FusionVector<T>>, delta_t: T) {
+        match mag {
+            Some(mag) => FusionAhrs::update(self, gyr, acc, mag, delta_t),
+            None => self.update_no_mag(gyr, acc, delta_t),
+        }
+    }
+
+    fn quaternion(&self) -> FusionQuaternion<T> {
+        self.quaternion
+    }
+
+    fn set_quaternion(&mut self, quaternion: FusionQuaternion<T>) {
+        FusionAhrs::set_quaternion(self, quaternion);
+    }
+
+    fn reset(&mut self) {
+        FusionAhrs::reset(self);
+    }
+
+    fn flags(&self) -> FusionAhrsFlags {
+        FusionAhrs::flags(self)
+    }
+}
+
+impl<T: FusionFloat> Default for FusionAhrs<T> {
+    fn default() -> Self {
+        let settings = FusionAhrsSettings::default();
+        let gain = settings.gain;
+        let recovery_trigger_period = settings.recovery_trigger_period;
+        Self {
+            settings,
+            quaternion: FusionQuaternion::identity(),
+            acc: FusionVector::zero(),
+            initialising: true,
+            ramped_gain: T::from_f64(INITIAL_GAIN),
+            ramped_gain_step: (T::from_f64(INITIAL_GAIN) - gain) / T::from_f64(INITIALISATION_PERIOD),
+            angular_rate_recovery: false,
+            half_accelerometer_feedback: FusionVector::zero(),
+            half_magnetometer_feedback: FusionVector::zero(),
+            accelerometer_ignored: false,
+            acceleration_recovery_trigger: 0,
+            acceleration_recovery_timeout: recovery_trigger_period,
+            magnetometer_ignored: false,
+            magnetic_recovery_trigger: 0,
+            magnetic_recovery_timeout: recovery_trigger_period,
+        }
+    }
 }
 
 impl FusionAhrsSettings {
+    /// Creates settings with default values, use `FusionAhrsSettings::default()` for other precisions.
     pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T: FusionFloat> Default for FusionAhrsSettings<T> {
+    fn default() -> Self {
         Self {
             convention: NWU,
-            gain: 0.5f32,
-            gyr_range: 0.0f32,
-            acc_rejection: 90.0f32,
-            mag_rejection: 90.0f32,
+            gain: T::HALF,
+            gyr_range: T::ZERO,
+            acc_rejection: T::from_f64(90.0f64),
+            mag_rejection: T::from_f64(90.0f64),
             recovery_trigger_period: 0,
         }
     }
 }
 
+/// Direction of gravity in the sensor frame scaled by 0.5.
+pub(crate) fn half_gravity<T: FusionFloat>(q: FusionQuaternion<T>, convention: FusionConvention) -> FusionVector<T> {
+    match convention {
+        FusionConvention::ENU | FusionConvention::NWU => {
+            FusionVector {
+                x: q.x * q.z - q.w * q.y,
+                y: q.y * q.z + q.w * q.x,
+                z: q.w * q.w - T::HALF + q.z * q.z,
+            }
+        }
+        FusionConvention::NED => {
+            FusionVector {
+                x: q.w * q.y - q.x * q.z,
+                y: -(q.y * q.z + q.w * q.x),
+                z: T::HALF - q.w * q.w - q.z * q.z,
+            }
+        }
+    }
+}
+
+/// Direction of the magnetic field's cross product with gravity in the sensor frame scaled by 0.5.
+pub(crate) fn half_magnetic<T: FusionFloat>(q: FusionQuaternion<T>, convention: FusionConvention) -> FusionVector<T> {
+    match convention {
+        FusionConvention::NWU => {
+            FusionVector {
+                x: q.x * q.y + q.w * q.z,
+                y: q.w * q.w - T::HALF + q.y * q.y,
+                z: q.y * q.z - q.w * q.x,
+            }
+        }
+        FusionConvention::ENU => {
+            FusionVector {
+                x: T::HALF - q.w * q.w - q.x * q.x,
+                y: q.w * q.z - q.x * q.y,
+                z: -(q.x * q.z + q.w * q.y),
+            }
+        }
+        FusionConvention::NED => {
+            FusionVector {
+                x: -(q.x * q.y + q.w * q.z),
+                y: T::HALF - q.w * q.w - q.y * q.y,
+                z: q.w * q.x - q.y * q.z,
+            }
+        }
+    }
+}
+
+pub(crate) fn feedback<T: FusionFloat>(sensor: FusionVector<T>, reference: FusionVector<T>) -> FusionVector<T> {
+    if sensor.dot_product(&reference) < T::ZERO { // if error is >90 degrees
+        sensor.cross_product(&reference).normalize()
+    } else {
+        sensor.cross_product(&reference)
+    }
+}
+
+pub(crate) fn check_inputs<T: FusionFloat>(gyr: FusionVector<T>, acc: FusionVector<T>, mag: FusionVector<T>, dt: T) -> Result<(), FusionError> {
+    if !gyr.is_finite() {
+        return Err(FusionError::NonFiniteGyroscope);
+    }
+    if !acc.is_finite() {
+        return Err(FusionError::NonFiniteAccelerometer);
+    }
+    if !mag.is_finite() {
+        return Err(FusionError::NonFiniteMagnetometer);
+    }
+    if !dt.is_finite() {
+        return Err(FusionError::NonFiniteDeltaTime);
+    }
+    Ok(())
+}
+
 fn clamp<T: Ord>(value: T, min: T, max: T) -> T {
     if value < min {
         min
@@ -346,3 +525,99 @@ fn clamp<T: Ord>(value: T, min: T, max: T) -> T {
         value
     }
 }
+
+#[cfg(feature = "libm")]
+#[test]
+fn internal_states_test() {
+    let mut ahrs = FusionAhrs::new();
+    let mut settings = FusionAhrsSettings::new();
+    settings.recovery_trigger_period = 10;
+    ahrs.update_settings(settings);
+    // Accelerometer tilted by 30 degrees from the initial (identity) attitude
+    let tilt = fusion_degrees_to_radians(30.0f32);
+    ahrs.update_no_mag(FusionVector::zero(), FusionVector::new(0.0f32, tilt.sin(), tilt.cos()), 0.01f32);
+    let states = ahrs.internal_states();
+    assert!((states.acceleration_error - 30.0f32).abs() < 0.01f32);
+    assert!(!states.accelerometer_ignored);
+    assert_eq!(states.acceleration_recovery_trigger, 0.0f32);
+    assert_eq!(states.magnetic_error, 0.0f32);
+    assert!(states.magnetometer_ignored);
+}
+
+#[cfg(feature = "libm")]
+#[test]
+fn set_euler_test() {
+    let mut ahrs = FusionAhrs::new();
+    ahrs.set_euler(FusionEuler { angle: Angle { roll: 10.0f32, pitch: -20.0f32, yaw: 30.0f32 } });
+    let euler = ahrs.quaternion.euler();
+    assert!((euler.angle.roll - 10.0f32).abs() < 0.01f32);
+    assert!((euler.angle.pitch + 20.0f32).abs() < 0.01f32);
+    assert!((euler.angle.yaw - 30.0f32).abs() < 0.01f32);
+    ahrs.set_heading(-45.0f32);
+    assert!((ahrs.quaternion.euler().angle.yaw + 45.0f32).abs() < 0.01f32);
+}
+
+#[cfg(feature = "libm")]
+#[test]
+fn initialise_from_test() {
+    for convention in [FusionConvention::NWU, FusionConvention::ENU, FusionConvention::NED] {
+        let mut ahrs = FusionAhrs::new();
+        let mut settings = FusionAhrsSettings::new();
+        settings.convention = convention;
+        ahrs.update_settings(settings);
+        ahrs.set_euler(FusionEuler { angle: Angle { roll: 15.0f32, pitch: 25.0f32, yaw: -60.0f32 } });
+        // Gravity and magnetic field (north with a downward inclination) as measured in the sensor frame
+        let acc = ahrs.calculate_half_gravity() * 2.0f32;
+        let west = ahrs.calculate_half_magnetic() * 2.0f32;
+        let mag = west.cross_product(&acc) - acc * 0.5f32;
+
+        ahrs.reset();
+        ahrs.initialise_from(acc, mag);
+        let euler = ahrs.quaternion.euler();
+        assert!((euler.angle.roll - 15.0f32).abs() < 0.01f32);
+        assert!((euler.angle.pitch - 25.0f32).abs() < 0.01f32);
+        assert!((euler.angle.yaw + 60.0f32).abs() < 0.01f32);
+        assert!(!ahrs.flags().initializing);
+    }
+    // Without an accelerometer measurement no attitude is known, so the gain ramp is kept
+    let mut ahrs = FusionAhrs::new();
+    ahrs.initialise_from(FusionVector::zero(), FusionVector::new(1.0f32, 0.0f32, 0.0f32));
+    assert!(ahrs.flags().initializing);
+    assert_eq!(ahrs.ramped_gain, INITIAL_GAIN as f32);
+    assert_eq!(ahrs.quaternion, FusionQuaternion::identity());
+}
+
+#[test]
+fn gravity_test() {
+    for convention in [FusionConvention::NWU, FusionConvention::ENU, FusionConvention::NED] {
+        let mut ahrs = FusionAhrs::new();
+        let mut settings = FusionAhrsSettings::new();
+        settings.convention = convention;
+        ahrs.update_settings(settings);
+        ahrs.set_euler(FusionEuler { angle: Angle { roll: 30.0f32, pitch: -10.0f32, yaw: 0.0f32 } });
+        let acc = FusionVector::new(0.1f32, 0.2f32, 0.3f32);
+        ahrs.acc = acc;
+        let reconstructed = ahrs.gravity() + ahrs.linear_acc();
+        assert!((reconstructed.x - acc.x).abs() < 0.0001f32);
+        assert!((reconstructed.y - acc.y).abs() < 0.0001f32);
+        assert!((reconstructed.z - acc.z).abs() < 0.0001f32);
+    }
+}
+
+#[test]
+fn try_update_test() {
+    let mut ahrs = FusionAhrs::new();
+    let acc = FusionVector::new(0.0f32, 0.0f32, 1.0f32);
+    ahrs.update_no_mag(FusionVector::new(10.0f32, 0.0f32, 0.0f32), acc, 0.01f32);
+    let quaternion = ahrs.quaternion;
+    assert_eq!(ahrs.try_update(FusionVector::new(f32::NAN, 0.0f32, 0.0f32), acc, FusionVector::zero(), 0.01f32), Err(FusionError::NonFiniteGyroscope));
+    assert_eq!(ahrs.try_update(FusionVector::zero(), FusionVector::new(0.0f32, f32::INFINITY, 0.0f32), FusionVector::zero(), 0.01f32), Err(FusionError::NonFiniteAccelerometer));
+    assert_eq!(ahrs.try_update(FusionVector::zero(), acc, FusionVector::new(0.0f32, 0.0f32, f32::NAN), 0.01f32), Err(FusionError::NonFiniteMagnetometer));
+    assert_eq!(ahrs.try_update_no_mag(FusionVector::zero(), acc, f32::NAN), Err(FusionError::NonFiniteDeltaTime));
+    assert_eq!(ahrs.try_update_external_heading(FusionVector::zero(), acc, f32::NAN, 0.01f32), Err(FusionError::NonFiniteHeading));
+    // Finite inputs that overflow the integration
+    let gyr = FusionVector::new(f32::MAX, f32::MAX, f32::MAX);
+    assert_eq!(ahrs.try_update_no_mag(gyr, acc, f32::MAX), Err(FusionError::NonFiniteQuaternion));
+    assert_eq!([ahrs.quaternion.w, ahrs.quaternion.x, ahrs.quaternion.y, ahrs.quaternion.z], [quaternion.w, quaternion.x, quaternion.y, quaternion.z]);
+    assert_eq!(ahrs.try_update_no_mag(FusionVector::zero(), acc, 0.01f32), Ok(()));
+}
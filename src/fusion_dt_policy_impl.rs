@@ -0,0 +1,64 @@
+use crate::{FusionDtPolicy, FusionDtStatus, FusionFloat};
+
+impl<T: FusionFloat> FusionDtPolicy<T> {
+    /// Creates a policy that uses every measured delta time as is.
+    ///
+    /// - `skip_first_sample` only initialises the timestamp on the first sample instead of integrating from zero.
+    /// - `max_dt` clamps larger delta times in seconds, zero disables the check.
+    /// - `reject_non_monotonic` skips samples whose timestamp did not increase.
+    /// - `nominal_fallback` uses the nominal delta time of 1 / sample rate instead of clamping or skipping.
+    pub fn new() -> Self {
+        Self {
+            skip_first_sample: false,
+            max_dt: T::ZERO,
+            reject_non_monotonic: false,
+            nominal_fallback: false,
+        }
+    }
+
+    /// Returns the delta time to integrate, or `None` if the update should be skipped, and the status of the policy.
+    pub fn apply(&self, delta_t: T, first_sample: bool, nominal_dt: T) -> (Option<T>, FusionDtStatus) {
+        if first_sample && self.skip_first_sample {
+            return (None, FusionDtStatus::FirstSampleSkipped);
+        }
+        if self.reject_non_monotonic && delta_t <= T::ZERO {
+            if self.nominal_fallback {
+                return (Some(nominal_dt), FusionDtStatus::NominalFallback);
+            }
+            return (None, FusionDtStatus::NonMonotonicRejected);
+        }
+        if self.max_dt > T::ZERO && delta_t > self.max_dt {
+            if self.nominal_fallback {
+                return (Some(nominal_dt), FusionDtStatus::NominalFallback);
+            }
+            return (Some(self.max_dt), FusionDtStatus::Clamped);
+        }
+        (Some(delta_t), FusionDtStatus::Measured)
+    }
+}
+
+impl<T: FusionFloat> Default for FusionDtPolicy<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[test]
+fn apply_test() {
+    let mut policy = FusionDtPolicy::new();
+    assert_eq!(policy.apply(100.0f32, true, 0.01f32), (Some(100.0f32), FusionDtStatus::Measured));
+    assert_eq!(policy.apply(-0.01f32, false, 0.01f32), (Some(-0.01f32), FusionDtStatus::Measured));
+
+    policy.skip_first_sample = true;
+    policy.max_dt = 0.1f32;
+    policy.reject_non_monotonic = true;
+    assert_eq!(policy.apply(100.0f32, true, 0.01f32), (None, FusionDtStatus::FirstSampleSkipped));
+    assert_eq!(policy.apply(0.02f32, false, 0.01f32), (Some(0.02f32), FusionDtStatus::Measured));
+    assert_eq!(policy.apply(0.5f32, false, 0.01f32), (Some(0.1f32), FusionDtStatus::Clamped));
+    assert_eq!(policy.apply(0.0f32, false, 0.01f32), (None, FusionDtStatus::NonMonotonicRejected));
+    assert_eq!(policy.apply(-0.01f32, false, 0.01f32), (None, FusionDtStatus::NonMonotonicRejected));
+
+    policy.nominal_fallback = true;
+    assert_eq!(policy.apply(0.5f32, false, 0.01f32), (Some(0.01f32), FusionDtStatus::NominalFallback));
+    assert_eq!(policy.apply(-0.01f32, false, 0.01f32), (Some(0.01f32), FusionDtStatus::NominalFallback));
+}
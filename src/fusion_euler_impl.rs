@@ -1,25 +1,65 @@
-use crate::{Angle, FusionEuler};
+use core::fmt;
+use crate::{Angle, FusionEuler, FusionFloat};
 
-impl FusionEuler {
+impl<T: FusionFloat> FusionEuler<T> {
     pub fn zero() -> Self {
-        const VALUE: FusionEuler =
-            FusionEuler {
-                angle: Angle {
-                    roll: 0.0f32,
-                    pitch: 0.0f32,
-                    yaw: 0.0f32,
-                }
-            };
-        VALUE
+        Self {
+            angle: Angle {
+                roll: T::ZERO,
+                pitch: T::ZERO,
+                yaw: T::ZERO,
+            }
+        }
     }
 
-    pub fn get_roll(&self) -> f32 {
+    pub fn get_roll(&self) -> T {
         self.angle.roll
     }
-    pub fn get_pitch(&self) -> f32 {
+    pub fn get_pitch(&self) -> T {
         self.angle.pitch
     }
-    pub fn get_yaw(&self) -> f32 {
+    pub fn get_yaw(&self) -> T {
         self.angle.yaw
     }
+
+    /// Returns true if each angle differs by at most epsilon, angles are not wrapped.
+    pub fn approx_eq(&self, rhs: &Self, epsilon: T) -> bool {
+        (self.angle.roll - rhs.angle.roll).abs() <= epsilon
+            && (self.angle.pitch - rhs.angle.pitch).abs() <= epsilon
+            && (self.angle.yaw - rhs.angle.yaw).abs() <= epsilon
+    }
+
+    /// Returns true if pitch in degrees is within tolerance degrees of +/-90, where roll and yaw are
+    /// rotations about the same axis and cannot be separated.
+    pub fn is_gimbal_locked(&self, tolerance: T) -> bool {
+        T::from_f64(90.0f64) - self.angle.pitch.abs() <= tolerance
+    }
+}
+
+impl<T: FusionFloat> Default for FusionEuler<T> {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+impl<T: FusionFloat> Default for Angle<T> {
+    fn default() -> Self {
+        Self {
+            roll: T::ZERO,
+            pitch: T::ZERO,
+            yaw: T::ZERO,
+        }
+    }
+}
+
+impl<T: FusionFloat> fmt::Display for FusionEuler<T> {
+    /// Formats as roll: r, pitch: p, yaw: y, applying the precision to each angle.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("roll: ")?;
+        fmt::Display::fmt(&self.angle.roll, f)?;
+        f.write_str(", pitch: ")?;
+        fmt::Display::fmt(&self.angle.pitch, f)?;
+        f.write_str(", yaw: ")?;
+        fmt::Display::fmt(&self.angle.yaw, f)
+    }
 }
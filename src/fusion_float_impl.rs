@@ -0,0 +1,146 @@
+#[cfg(feature = "libm")]
+use libm::{asin, atan2, cos, fabs, sin, sqrt};
+#[cfg(feature = "libm")]
+use libm::{asinf, atan2f, cosf, fabsf, sinf, sqrtf};
+// micromath is only a fallback, so that enabling both backends keeps the accuracy of libm
+#[cfg(all(feature = "micromath", not(feature = "libm")))]
+use micromath_f32::{asinf, atan2f, cosf, fabsf, sinf, sqrtf};
+#[allow(unused_imports)]
+use crate::{fusion_fast_inverse_sqrt, FusionFloat};
+
+impl FusionFloat for f32 {
+    const ZERO: Self = 0.0f32;
+    const ONE: Self = 1.0f32;
+    const HALF: Self = 0.5f32;
+    const TWO: Self = 2.0f32;
+    const PI: Self = core::f32::consts::PI;
+    const MAX: Self = f32::MAX;
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn is_finite(self) -> bool {
+        f32::is_finite(self)
+    }
+
+    fn abs(self) -> Self {
+        fabsf(self)
+    }
+
+    fn sqrt(self) -> Self {
+        sqrtf(self)
+    }
+
+    fn inverse_sqrt(self) -> Self {
+        #[cfg(feature = "fusion-use-normal-sqrt")]
+        {
+            1.0f32 / sqrtf(self)
+        }
+        #[cfg(not(feature = "fusion-use-normal-sqrt"))]
+        {
+            fusion_fast_inverse_sqrt(self)
+        }
+    }
+
+    fn sin(self) -> Self {
+        sinf(self)
+    }
+
+    fn cos(self) -> Self {
+        cosf(self)
+    }
+
+    fn asin(self) -> Self {
+        asinf(self)
+    }
+
+    fn atan2(self, other: Self) -> Self {
+        atan2f(self, other)
+    }
+}
+
+#[cfg(feature = "libm")]
+impl FusionFloat for f64 {
+    const ZERO: Self = 0.0f64;
+    const ONE: Self = 1.0f64;
+    const HALF: Self = 0.5f64;
+    const TWO: Self = 2.0f64;
+    const PI: Self = core::f64::consts::PI;
+    const MAX: Self = f64::MAX;
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn is_finite(self) -> bool {
+        f64::is_finite(self)
+    }
+
+    fn abs(self) -> Self {
+        fabs(self)
+    }
+
+    fn sqrt(self) -> Self {
+        sqrt(self)
+    }
+
+    fn inverse_sqrt(self) -> Self {
+        // The fast inverse square root approximation is only defined for f32
+        1.0f64 / sqrt(self)
+    }
+
+    fn sin(self) -> Self {
+        sin(self)
+    }
+
+    fn cos(self) -> Self {
+        cos(self)
+    }
+
+    fn asin(self) -> Self {
+        asin(self)
+    }
+
+    fn atan2(self, other: Self) -> Self {
+        atan2(self, other)
+    }
+}
+
+// libm style names for the micromath f32 functions
+#[cfg(all(feature = "micromath", not(feature = "libm")))]
+mod micromath_f32 {
+    use micromath::F32Ext;
+
+    pub fn asinf(x: f32) -> f32 {
+        F32Ext::asin(x)
+    }
+
+    pub fn atan2f(y: f32, x: f32) -> f32 {
+        F32Ext::atan2(y, x)
+    }
+
+    pub fn cosf(x: f32) -> f32 {
+        F32Ext::cos(x)
+    }
+
+    pub fn fabsf(x: f32) -> f32 {
+        F32Ext::abs(x)
+    }
+
+    pub fn sinf(x: f32) -> f32 {
+        F32Ext::sin(x)
+    }
+
+    pub fn sqrtf(x: f32) -> f32 {
+        F32Ext::sqrt(x)
+    }
+}
@@ -1,33 +1,223 @@
-use libm::{fabsf};
-use crate::{CUTOFF_FREQUENCY, FusionGyrOffset, FusionVector, THRESHOLD, TIMEOUT};
+use crate::{CUTOFF_FREQUENCY, DEFAULT_SAMPLE_RATE, FUSION_GYR_OFFSET_STATE_SIZE, FUSION_GYR_OFFSET_STATE_VERSION, FusionError, FusionFloat, FusionGyrOffset, FusionGyrOffsetSettings, FusionVector, THRESHOLD, TIMEOUT};
 
-impl FusionGyrOffset {
+impl<T: FusionFloat> FusionGyrOffset<T> {
+    /// Creates gyroscope offset correction with default algorithm parameters for the sample rate in Hz.
     pub fn new(sample_rate: u32) -> Self {
-        Self {
-            filter_coefficient: 2.0f32 * core::f32::consts::PI * CUTOFF_FREQUENCY * (1.0f32 / sample_rate as f32),
+        Self::with_settings(sample_rate, FusionGyrOffsetSettings::default())
+    }
+
+    /// Creates gyroscope offset correction with custom algorithm parameters.
+    /// # Examples
+    /// ```
+    /// use imu_fusion::{FusionGyrOffset, FusionGyrOffsetSettings};
+    ///
+    /// let mut settings = FusionGyrOffsetSettings::new();
+    /// settings.threshold = 0.5f32;
+    /// settings.timeout = 10.0f32;
+    /// let offset = FusionGyrOffset::with_settings(100, settings);
+    /// assert_eq!(offset.timeout, 1000);
+    /// ```
+    pub fn with_settings(sample_rate: u32, settings: FusionGyrOffsetSettings<T>) -> Self {
+        let mut offset = Self {
+            settings: settings.clone(),
+            sample_rate,
+            filter_coefficient: T::ZERO,
             gyroscope_offset: FusionVector::zero(),
-            timeout: TIMEOUT * sample_rate,
+            timeout: 0,
             timer: 0,
-        }
+        };
+        offset.update_settings(settings);
+        offset
     }
 
-    pub fn update(&mut self, mut gyr: FusionVector) -> FusionVector {
-        // Subtract offset from gyroscope measurement
-        gyr = gyr - self.gyroscope_offset;
-        // Reset timer if gyroscope not stationary
+    /// Changes the algorithm parameters, the current offset and stationary timer are kept.
+    pub fn update_settings(&mut self, settings: FusionGyrOffsetSettings<T>) {
+        (self.filter_coefficient, self.timeout) = settings.coefficients(self.sample_rate);
+        self.settings = settings;
+    }
 
-        if fabsf(gyr.x) > THRESHOLD || fabsf(gyr.y) > THRESHOLD || fabsf(gyr.z) > THRESHOLD {
-            self.timer = 0;
-            return gyr;
-        }
+    /// Returns true if the gyroscope has been stationary for the timeout and the offset is being adjusted.
+    pub fn is_adapting(&self) -> bool {
+        self.timer >= self.timeout
+    }
 
-        // Increment timer while gyroscope stationary
-        if self.timer < self.timeout {
-            self.timer += 1;
+    pub fn update(&mut self, mut gyr: FusionVector<T>) -> FusionVector<T> {
+        // Subtract offset from gyroscope measurement
+        gyr -= self.gyroscope_offset;
+        if !self.settings.update_stationary_timer(&mut self.timer, self.timeout, gyr) {
             return gyr;
         }
         // Adjust offset if timer has elapsed
-        self.gyroscope_offset = self.gyroscope_offset + (gyr * self.filter_coefficient);
+        self.gyroscope_offset += gyr * self.filter_coefficient;
         gyr
     }
-}
\ No newline at end of file
+
+    /// Saves the learned offset, stationary timer and sample rate as a fixed-size little-endian byte array,
+    /// e.g. to be written to flash or EEPROM.
+    /// # Examples
+    /// ```
+    /// use imu_fusion::FusionGyrOffset;
+    ///
+    /// let mut offset: FusionGyrOffset = FusionGyrOffset::new(100);
+    /// offset.gyroscope_offset.x = 0.25f32;
+    /// let bytes = offset.save();
+    ///
+    /// let mut restored: FusionGyrOffset = FusionGyrOffset::new(100);
+    /// restored.restore(&bytes).unwrap();
+    /// assert_eq!(restored.gyroscope_offset.x, 0.25f32);
+    /// ```
+    pub fn save(&self) -> [u8; FUSION_GYR_OFFSET_STATE_SIZE] {
+        let mut bytes = [0u8; FUSION_GYR_OFFSET_STATE_SIZE];
+        bytes[0] = FUSION_GYR_OFFSET_STATE_VERSION;
+        bytes[1..5].copy_from_slice(&self.sample_rate.to_le_bytes());
+        bytes[5..9].copy_from_slice(&self.timer.to_le_bytes());
+        bytes[9..13].copy_from_slice(&(self.gyroscope_offset.x.to_f64() as f32).to_le_bytes());
+        bytes[13..17].copy_from_slice(&(self.gyroscope_offset.y.to_f64() as f32).to_le_bytes());
+        bytes[17..21].copy_from_slice(&(self.gyroscope_offset.z.to_f64() as f32).to_le_bytes());
+        bytes[21] = crc8(&bytes[..21]);
+        bytes
+    }
+
+    /// Restores state written by `save`. The settings are kept, and the state is left unchanged if the bytes
+    /// have a different version or sample rate, or are corrupt.
+    pub fn restore(&mut self, bytes: &[u8; FUSION_GYR_OFFSET_STATE_SIZE]) -> Result<(), FusionError> {
+        let read_u32 = |i: usize| u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        let read_f32 = |i: usize| T::from_f64(f32::from_bits(read_u32(i)) as f64);
+        if bytes[0] != FUSION_GYR_OFFSET_STATE_VERSION {
+            return Err(FusionError::GyrOffsetVersionMismatch);
+        }
+        if crc8(&bytes[..21]) != bytes[21] {
+            return Err(FusionError::InvalidSavedState);
+        }
+        if read_u32(1) != self.sample_rate {
+            return Err(FusionError::GyrOffsetSampleRateMismatch);
+        }
+        let gyroscope_offset = FusionVector::new(read_f32(9), read_f32(13), read_f32(17));
+        if !gyroscope_offset.is_finite() {
+            return Err(FusionError::InvalidSavedState);
+        }
+        self.timer = read_u32(5).min(self.timeout);
+        self.gyroscope_offset = gyroscope_offset;
+        Ok(())
+    }
+}
+
+/// CRC-8 with polynomial 0x07, detects corruption of the saved state e.g. by a partially written flash page.
+fn crc8(bytes: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for byte in bytes {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+impl<T: FusionFloat> FusionGyrOffsetSettings<T> {
+    // Returns the low-pass filter coefficient and the timeout in samples for a sample rate.
+    pub(crate) fn coefficients(&self, sample_rate: u32) -> (T, u32) {
+        let sample_rate = T::from_f64(sample_rate as f64);
+        let filter_coefficient = T::TWO * T::PI * self.cutoff_frequency * (T::ONE / sample_rate);
+        (filter_coefficient, (self.timeout * sample_rate).to_f64() as u32)
+    }
+
+    // Stationary detection shared by the offset algorithms, returns true once the offset corrected gyroscope has been
+    // within the threshold for the timeout.
+    pub(crate) fn update_stationary_timer(&self, timer: &mut u32, timeout: u32, corrected_gyr: FusionVector<T>) -> bool {
+        // Reset timer if gyroscope not stationary
+        let threshold = self.threshold;
+        if corrected_gyr.x.abs() > threshold || corrected_gyr.y.abs() > threshold || corrected_gyr.z.abs() > threshold {
+            *timer = 0;
+            return false;
+        }
+
+        // Increment timer while gyroscope stationary
+        if *timer < timeout {
+            *timer += 1;
+            return false;
+        }
+        true
+    }
+}
+
+/// Uses a nominal sample rate of 100 Hz, use `FusionGyrOffset::new` with the actual sample rate otherwise, as the
+/// timeout and filter coefficient depend on it.
+impl<T: FusionFloat> Default for FusionGyrOffset<T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_SAMPLE_RATE)
+    }
+}
+
+impl FusionGyrOffsetSettings {
+    /// Creates settings with default values, use `FusionGyrOffsetSettings::default()` for other precisions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T: FusionFloat> Default for FusionGyrOffsetSettings<T> {
+    fn default() -> Self {
+        Self {
+            threshold: T::from_f64(THRESHOLD),
+            timeout: T::from_f64(TIMEOUT),
+            cutoff_frequency: T::from_f64(CUTOFF_FREQUENCY),
+        }
+    }
+}
+
+#[test]
+fn settings_test() {
+    let mut offset = FusionGyrOffset::with_settings(100, FusionGyrOffsetSettings { threshold: 0.1f32, timeout: 0.05f32, ..Default::default() });
+    let gyr = FusionVector::new(0.2f32, 0.0f32, 0.0f32);
+    for _ in 0..10 {
+        offset.update(gyr);
+    }
+    assert!(!offset.is_adapting());
+    offset.update_settings(FusionGyrOffsetSettings::default());
+    for _ in 0..5 {
+        offset.update(gyr);
+    }
+    assert_eq!(offset.timeout, 500);
+    assert!(!offset.is_adapting());
+    offset.update_settings(FusionGyrOffsetSettings { timeout: 0.05f32, ..Default::default() });
+    offset.update(gyr);
+    assert!(offset.is_adapting());
+    assert!(offset.gyroscope_offset.x > 0.0f32);
+}
+
+#[test]
+fn default_test() {
+    let offset: FusionGyrOffset = FusionGyrOffset::default();
+    assert_eq!(offset, FusionGyrOffset::new(100));
+    assert_eq!(offset.timeout, 500);
+}
+
+#[cfg(feature = "libm")]
+#[test]
+fn restore_test() {
+    let mut offset = FusionGyrOffset::<f64>::new(100);
+    offset.gyroscope_offset = FusionVector::new(0.5f64, -1.25f64, 2.0f64);
+    offset.timer = 42;
+    let mut bytes = offset.save();
+
+    let mut restored = FusionGyrOffset::<f64>::new(100);
+    assert_eq!(restored.restore(&bytes), Ok(()));
+    assert_eq!(restored.timer, 42);
+    assert_eq!(restored.gyroscope_offset.y, -1.25f64);
+    assert_eq!(FusionGyrOffset::<f64>::new(200).restore(&bytes), Err(FusionError::GyrOffsetSampleRateMismatch));
+
+    bytes[0] = FUSION_GYR_OFFSET_STATE_VERSION + 1;
+    let mut unchanged = FusionGyrOffset::<f64>::new(100);
+    assert_eq!(unchanged.restore(&bytes), Err(FusionError::GyrOffsetVersionMismatch));
+    assert_eq!(unchanged.gyroscope_offset.y, 0.0f64);
+
+    let mut bytes = offset.save();
+    bytes[10] ^= 0x01;
+    assert_eq!(unchanged.restore(&bytes), Err(FusionError::InvalidSavedState));
+
+    // A non-finite offset with a valid checksum is still rejected
+    offset.gyroscope_offset.z = f64::NAN;
+    assert_eq!(unchanged.restore(&offset.save()), Err(FusionError::InvalidSavedState));
+    assert_eq!(unchanged.gyroscope_offset.y, 0.0f64);
+}
@@ -0,0 +1,154 @@
+use crate::{FUSION_GYR_TEMP_OFFSET_BINS, FusionFloat, FusionGyrOffsetSettings, FusionGyrTempOffset, FusionGyrTempOffsetSettings, FusionVector};
+
+impl<T: FusionFloat> FusionGyrTempOffset<T> {
+    /// Creates temperature-compensated gyroscope offset correction with default parameters for the sample rate in Hz.
+    pub fn new(sample_rate: u32) -> Self {
+        Self::with_settings(sample_rate, FusionGyrTempOffsetSettings::default())
+    }
+
+    /// Creates temperature-compensated gyroscope offset correction with custom parameters.
+    /// # Examples
+    /// ```
+    /// use imu_fusion::{FusionGyrTempOffset, FusionGyrTempOffsetSettings, FusionVector};
+    ///
+    /// let mut settings = FusionGyrTempOffsetSettings::new();
+    /// settings.min_temperature = -40.0f32;
+    /// settings.max_temperature = 85.0f32;
+    /// let mut offset = FusionGyrTempOffset::with_settings(100, settings);
+    ///
+    /// let gyr = FusionVector::new(0.1f32, -0.2f32, 0.05f32);
+    /// let temperature = 21.5f32;
+    /// let corrected_gyr = offset.update(gyr, temperature);
+    /// ```
+    pub fn with_settings(sample_rate: u32, settings: FusionGyrTempOffsetSettings<T>) -> Self {
+        let mut offset = Self {
+            settings: settings.clone(),
+            sample_rate,
+            filter_coefficient: T::ZERO,
+            timeout: 0,
+            timer: 0,
+            bins: [FusionVector::zero(); FUSION_GYR_TEMP_OFFSET_BINS],
+            bin_temperatures: [T::ZERO; FUSION_GYR_TEMP_OFFSET_BINS],
+            learned: [false; FUSION_GYR_TEMP_OFFSET_BINS],
+        };
+        offset.update_settings(settings);
+        offset
+    }
+
+    /// Changes the parameters, the learned bins are kept but are only meaningful if the temperature range is unchanged.
+    pub fn update_settings(&mut self, settings: FusionGyrTempOffsetSettings<T>) {
+        (self.filter_coefficient, self.timeout) = settings.offset.coefficients(self.sample_rate);
+        self.settings = settings;
+    }
+
+    /// Returns true if the gyroscope has been stationary for the timeout and the offset is being adjusted.
+    pub fn is_adapting(&self) -> bool {
+        self.timer >= self.timeout
+    }
+
+    /// Returns the offset for a temperature, linearly interpolated between the learned bins either side of it.
+    pub fn offset(&self, temperature: T) -> FusionVector<T> {
+        let mut lower: Option<usize> = None;
+        for i in (0..FUSION_GYR_TEMP_OFFSET_BINS).filter(|&i| self.learned[i]) {
+            if self.bin_temperatures[i] >= temperature {
+                return match lower {
+                    Some(lower) if self.bin_temperatures[i] > self.bin_temperatures[lower] => {
+                        let fraction = (temperature - self.bin_temperatures[lower]) / (self.bin_temperatures[i] - self.bin_temperatures[lower]);
+                        self.bins[lower] + (self.bins[i] - self.bins[lower]) * fraction
+                    }
+                    _ => self.bins[i],
+                };
+            }
+            lower = Some(i);
+        }
+        lower.map_or(FusionVector::zero(), |lower| self.bins[lower])
+    }
+
+    /// Corrects the gyroscope measurement in degrees/s with the offset for a temperature, and learns the offset of the
+    /// nearest bin while stationary.
+    ///
+    /// The first stationary sample seeds a bin with the raw measurement and temperature rather than blending them
+    /// through the filter from zero, so that a new bin starts near the true offset and is then low-pass filtered.
+    pub fn update(&mut self, gyr: FusionVector<T>, temperature: T) -> FusionVector<T> {
+        // Subtract offset for the current temperature from gyroscope measurement
+        let corrected_gyr = gyr - self.offset(temperature);
+        if !self.settings.offset.update_stationary_timer(&mut self.timer, self.timeout, corrected_gyr) {
+            return corrected_gyr;
+        }
+        // Adjust offset and temperature of the nearest bin if timer has elapsed
+        let i = self.nearest_bin(temperature);
+        if self.learned[i] {
+            self.bins[i] += (gyr - self.bins[i]) * self.filter_coefficient;
+            self.bin_temperatures[i] += (temperature - self.bin_temperatures[i]) * self.filter_coefficient;
+        } else {
+            // Seed a new bin with the measurement instead of filtering from zero
+            self.bins[i] = gyr;
+            self.bin_temperatures[i] = temperature;
+            self.learned[i] = true;
+        }
+        corrected_gyr
+    }
+
+    // Returns the index of the bin nearest to a temperature, temperatures outside the range use the end bins.
+    fn nearest_bin(&self, temperature: T) -> usize {
+        let last = (FUSION_GYR_TEMP_OFFSET_BINS - 1) as f64;
+        let range = self.settings.max_temperature - self.settings.min_temperature;
+        let position = ((temperature - self.settings.min_temperature) / range).to_f64() * last + 0.5f64;
+        if position > last {
+            FUSION_GYR_TEMP_OFFSET_BINS - 1
+        } else if position > 0.0f64 {
+            position as usize
+        } else {
+            0
+        }
+    }
+}
+
+impl FusionGyrTempOffsetSettings {
+    /// Creates settings with default values, use `FusionGyrTempOffsetSettings::default()` for other precisions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T: FusionFloat> Default for FusionGyrTempOffsetSettings<T> {
+    fn default() -> Self {
+        Self {
+            offset: FusionGyrOffsetSettings::default(),
+            min_temperature: T::from_f64(-20.0f64),
+            max_temperature: T::from_f64(60.0f64),
+        }
+    }
+}
+
+#[cfg(feature = "libm")]
+#[test]
+fn temperature_test() {
+    let mut offset = FusionGyrTempOffset::<f64>::new(100);
+    let bias = |temperature: f64| FusionVector::new(0.02f64 * temperature + 0.5f64, -0.01f64 * temperature, 0.0f64);
+    for step in 0..=40 {
+        let temperature = -20.0f64 + 2.0f64 * step as f64;
+        for _ in 0..1000 {
+            offset.update(bias(temperature), temperature);
+        }
+    }
+    assert!(offset.is_adapting());
+    for temperature in [-15.0f64, 0.0f64, 25.0f64, 42.5f64] {
+        let error = offset.offset(temperature) - bias(temperature);
+        assert!(error.x.abs() < 0.001f64 && error.y.abs() < 0.001f64);
+    }
+}
+
+#[cfg(feature = "libm")]
+#[test]
+fn seed_test() {
+    let mut offset = FusionGyrTempOffset::<f64>::new(100);
+    let gyr = FusionVector::new(0.5f64, -0.25f64, 0.1f64);
+    for _ in 0..=offset.timeout {
+        offset.update(gyr, 20.0f64);
+    }
+    // The first learned sample seeds the bin, later samples are filtered
+    assert_eq!(offset.offset(20.0f64), gyr);
+    offset.update(gyr + FusionVector::new(1.0f64, 0.0f64, 0.0f64), 20.0f64);
+    assert_eq!(offset.offset(20.0f64).x, 0.5f64 + offset.filter_coefficient);
+}
@@ -1,9 +1,27 @@
-use crate::{Fusion, FusionAhrs, FusionAhrsSettings, FusionEuler, FusionGyrOffset, FusionMatrix, FusionQuaternion, FusionVector};
+use crate::axes::swap;
+use crate::fusion_ahrs_impl::check_inputs;
+use crate::{Fusion, FusionAhrs, FusionAhrsFlags, FusionAxesAlignment, FusionAhrsInternalStates, FusionAhrsSettings, FusionDtPolicy, FusionDtStatus, FusionError, FusionEuler, FusionFloat, FusionGyrOffset, FusionGyrTempOffset, FusionMatrix, FusionQuaternion, FusionTimestamp, FusionVector, OrientationFilter};
 
-impl Fusion {
-    pub fn new(sample_rate: u32, ahrs_settings: FusionAhrsSettings) -> Self {
-        let mut ahrs = FusionAhrs::new();
-        ahrs.update_settings(ahrs_settings);
+impl<T: FusionFloat, F: OrientationFilter<T>> Fusion<T, F> {
+    /// Creates the sensor fusion pipeline (axes alignment, calibration, gyroscope offset correction and timestamp
+    /// handling) driving any orientation filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use imu_fusion::{Fusion, FusionAhrs, FusionAhrsSettings, FusionVector};
+    ///
+    /// const SAMPLE_RATE_HZ: u32 = 100;
+    ///
+    /// let mut ahrs = FusionAhrs::new();
+    /// ahrs.update_settings(FusionAhrsSettings::new());
+    /// let mut fusion = Fusion::with_filter(SAMPLE_RATE_HZ, ahrs);
+    ///
+    /// let gyr = FusionVector::new(0f32, 0f32, 0f32); // replace this with actual gyroscope data in degrees/s
+    /// let acc = FusionVector::new(0f32, 0f32, 1.0f32); // replace this with actual accelerometer data in g
+    /// fusion.update_no_mag(gyr, acc, 0.01f32);
+    /// ```
+    pub fn with_filter(sample_rate: u32, filter: F) -> Self {
         Self {
             gyr_misalignment: FusionMatrix::identity(),
             gyr_sensitivity: FusionVector::ones(),
@@ -13,24 +31,33 @@ impl Fusion {
             acc_offset: FusionVector::zero(),
             soft_iron_matrix: FusionMatrix::identity(),
             hard_iron_offset: FusionVector::zero(),
-            ahrs,
+            gyr_alignment: FusionAxesAlignment::PXPYPZ,
+            acc_alignment: FusionAxesAlignment::PXPYPZ,
+            mag_alignment: FusionAxesAlignment::PXPYPZ,
+            ahrs: filter,
             offset: FusionGyrOffset::new(sample_rate),
-            last_timestamp: 0f32,
+            temp_offset: None,
+            temperature: T::ZERO,
+            sample_rate,
+            dt_policy: FusionDtPolicy::new(),
+            last_timestamp: None,
         }
     }
 
-    pub fn inertial_calibration(&self, uncalibrated: FusionVector, misalignment: FusionMatrix, sensitivity: FusionVector, offset: FusionVector) -> FusionVector {
+    pub fn inertial_calibration(&self, uncalibrated: FusionVector<T>, misalignment: FusionMatrix<T>, sensitivity: FusionVector<T>, offset: FusionVector<T>) -> FusionVector<T> {
         misalignment * ((uncalibrated - offset) * sensitivity)
     }
 
-    pub fn magnetic_calibration(&self, uncalibrated: FusionVector, soft_iron_matrix: FusionMatrix, hard_iron_offset: FusionVector) -> FusionVector {
+    pub fn magnetic_calibration(&self, uncalibrated: FusionVector<T>, soft_iron_matrix: FusionMatrix<T>, hard_iron_offset: FusionVector<T>) -> FusionVector<T> {
         soft_iron_matrix * (uncalibrated - hard_iron_offset)
     }
 
     /// Updates the AHRS algorithm based on gyroscope data in degrees/s and acceleration data in g force.
     ///
     /// The time is provided using an absolute timestamp in seconds since the first measurement.
-    /// Note that if you provide unix timestamps, the precision of f32 will not be enough to correctly compute the time difference.
+    /// The delta time is checked against `dt_policy` and the returned status reports which policy was applied.
+    /// Note that if you provide unix timestamps, the precision of f32 will not be enough to correctly compute the time difference,
+    /// use the `_micros` or `_secs_f64` variants instead.
     ///
     /// # Examples
     /// ```no_run
@@ -50,10 +77,12 @@ impl Fusion {
     /// }
     ///
     /// ```
-    pub fn update_no_mag(&mut self, gyr: FusionVector, acc: FusionVector, timestamp: f32) {
-        let delta_t = timestamp - self.last_timestamp;
-        self.update_no_mag_by_duration_seconds(gyr, acc, delta_t);
-        self.last_timestamp = timestamp;
+    pub fn update_no_mag(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, timestamp: T) -> FusionDtStatus {
+        let (delta_t, status) = self.advance_timestamp(FusionTimestamp::Seconds(timestamp.to_f64()));
+        if let Some(delta_t) = delta_t {
+            self.update_no_mag_by_duration_seconds(gyr, acc, delta_t);
+        }
+        status
     }
 
     /// Updates the AHRS algorithm based on gyroscope data in degrees/s and acceleration data in g force.
@@ -80,25 +109,31 @@ impl Fusion {
     /// }
     ///
     /// ```
-    pub fn update_no_mag_by_duration_seconds(&mut self, gyr: FusionVector, acc: FusionVector, delta_t: f32) {
+    pub fn update_no_mag_by_duration_seconds(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, delta_t: T) {
+        // Apply axes alignment
+        let gyr = swap(gyr, self.gyr_alignment);
+        let acc = swap(acc, self.acc_alignment);
+
         // Apply calibration
         let mut gyr = self.inertial_calibration(gyr, self.gyr_misalignment, self.gyr_sensitivity, self.gyr_offset);
         let acc = self.inertial_calibration(acc, self.acc_misalignment, self.acc_sensitivity, self.acc_offset);
 
         // Update gyroscope offset correction algorithm
-        gyr = self.offset.update(gyr);
+        gyr = self.update_gyr_offset(gyr);
 
-        self.ahrs.update_no_mag(gyr, acc, delta_t);
+        self.ahrs.update(gyr, acc, None, delta_t);
     }
 
-    /// Updates the AHRS algorithm based on gyroscope data in degrees/s, acceleration data in g force and a heading in degrees.
+    /// Updates the AHRS algorithm based on gyroscope data in degrees/s, acceleration data in g force and magnetic measurements in degrees.
     ///
     /// The time is provided using an absolute timestamp in seconds since the first measurement.
-    /// Note that if you provide unix timestamps, the precision of f32 will not be enough to correctly compute the time difference.
+    /// The delta time is checked against `dt_policy` and the returned status reports which policy was applied.
+    /// Note that if you provide unix timestamps, the precision of f32 will not be enough to correctly compute the time difference,
+    /// use the `_micros` or `_secs_f64` variants instead.
     ///
     /// # Examples
     /// ```no_run
-    /// use imu_fusion::{Fusion, FusionVector, FusionAhrsSettings};
+    /// use imu_fusion::{Fusion, FusionAhrsSettings, FusionVector};
     ///
     /// const SAMPLE_RATE_HZ: u32 = 100;
     ///
@@ -109,32 +144,33 @@ impl Fusion {
     /// loop {
     ///     let gyr = FusionVector::new(0f32, 0f32, 0f32); // replace this with actual gyroscope data in degrees/s
     ///     let acc = FusionVector::new(0f32, 0f32, 1.0f32); // replace this with actual accelerometer data in g
-    ///     let external_heading = 0f32; // replace this with actual heading in degrees
-    ///     fusion.update_external_heading(gyr, acc, external_heading, current_ts);
+    ///     let mag = FusionVector::new(0f32, 0f32, 1.0f32); // replace this with actual magnetic measurement in degrees
+    ///     fusion.update(gyr, acc, mag, current_ts);
     ///     current_ts += 1.0 / SAMPLE_RATE_HZ as f32
     /// }
     ///
     /// ```
-    pub fn update_external_heading(
+    pub fn update(
         &mut self,
-        gyr: FusionVector,
-        acc: FusionVector,
-        heading: f32,
-        timestamp: f32,
-    ) {
-        let delta_t = timestamp - self.last_timestamp;
-        self.update_external_heading_by_duration_seconds(gyr, acc, heading, delta_t);
-        self.last_timestamp = timestamp;
+        gyr: FusionVector<T>,
+        acc: FusionVector<T>,
+        mag: FusionVector<T>,
+        timestamp: T,
+    ) -> FusionDtStatus {
+        let (delta_t, status) = self.advance_timestamp(FusionTimestamp::Seconds(timestamp.to_f64()));
+        if let Some(delta_t) = delta_t {
+            self.update_by_duration_seconds(gyr, acc, mag, delta_t);
+        }
+        status
     }
 
-    /// Updates the AHRS algorithm based on gyroscope data in degrees/s, acceleration data in g force and a heading in degrees.
+    /// Updates the AHRS algorithm based on gyroscope data in degrees/s, acceleration data in g force and magnetic measurements in degrees.
     ///
     /// The time is provided using a duration in seconds since the last measurement.
     /// Note that this won't increase the internal timestamp and using the timestamp version of update functions will produce incorrect results.
-    ///
     /// # Examples
     /// ```no_run
-    /// use imu_fusion::{Fusion, FusionVector, FusionAhrsSettings};
+    /// use imu_fusion::{Fusion, FusionAhrsSettings, FusionVector};
     /// use std::time::{Duration, Instant};
     ///
     /// const SAMPLE_RATE_HZ: u32 = 100;
@@ -146,72 +182,511 @@ impl Fusion {
     /// loop {
     ///     let gyr = FusionVector::new(0f32, 0f32, 0f32); // replace this with actual gyroscope data in degrees/s
     ///     let acc = FusionVector::new(0f32, 0f32, 1.0f32); // replace this with actual accelerometer data in g
-    ///     let external_heading = 0f32; // replace this with actual heading in degrees
-    ///     fusion.update_external_heading(gyr, acc, external_heading, last_ts.elapsed().as_secs_f32());
+    ///     let mag = FusionVector::new(0f32, 0f32, 1.0f32); // replace this with actual magnetic measurement in degrees
+    ///     fusion.update_by_duration_seconds(gyr, acc, mag, last_ts.elapsed().as_secs_f32());
     ///     last_ts = Instant::now();
     /// }
     ///
     /// ```
-    pub fn update_external_heading_by_duration_seconds(
+    pub fn update_by_duration_seconds(
         &mut self,
-        gyr: FusionVector,
-        acc: FusionVector,
-        heading: f32,
-        delta_t: f32,
+        gyr: FusionVector<T>,
+        acc: FusionVector<T>,
+        mag: FusionVector<T>,
+        delta_t: T,
     ) {
+        // Apply axes alignment
+        let gyr = swap(gyr, self.gyr_alignment);
+        let acc = swap(acc, self.acc_alignment);
+        let mag = swap(mag, self.mag_alignment);
+
         // Apply calibration
         let mut gyr = self.inertial_calibration(gyr, self.gyr_misalignment, self.gyr_sensitivity, self.gyr_offset);
         let acc = self.inertial_calibration(acc, self.acc_misalignment, self.acc_sensitivity, self.acc_offset);
+        let mag = self.magnetic_calibration(mag, self.soft_iron_matrix, self.hard_iron_offset);
 
         // Update gyroscope offset correction algorithm
-        gyr = self.offset.update(gyr);
+        gyr = self.update_gyr_offset(gyr);
 
-        self.ahrs.update_external_heading(gyr, acc, heading, delta_t);
+        self.ahrs.update(gyr, acc, Some(mag), delta_t);
+    }
+
+    /// Updates the AHRS algorithm based on gyroscope data in degrees/s and acceleration data in g force.
+    ///
+    /// The time is provided using an absolute timestamp in microseconds.
+    /// The delta time is checked against `dt_policy` and the returned status reports which policy was applied.
+    pub fn update_no_mag_micros(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, timestamp_us: u64) -> FusionDtStatus {
+        let (delta_t, status) = self.advance_timestamp(FusionTimestamp::Microseconds(timestamp_us));
+        if let Some(delta_t) = delta_t {
+            self.update_no_mag_by_duration_seconds(gyr, acc, delta_t);
+        }
+        status
+    }
+
+    /// Updates the AHRS algorithm based on gyroscope data in degrees/s and acceleration data in g force.
+    ///
+    /// The time is provided using a wrapping 32-bit tick counter in microseconds, see `update_micros_u32`.
+    /// The delta time is checked against `dt_policy` and the returned status reports which policy was applied.
+    pub fn update_no_mag_micros_u32(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, timestamp_us: u32) -> FusionDtStatus {
+        let timestamp = self.following_micros_u32(timestamp_us);
+        let (delta_t, status) = self.advance_timestamp(timestamp);
+        if let Some(delta_t) = delta_t {
+            self.update_no_mag_by_duration_seconds(gyr, acc, delta_t);
+        }
+        status
+    }
+
+    /// Updates the AHRS algorithm based on gyroscope data in degrees/s and acceleration data in g force.
+    ///
+    /// The time is provided using an absolute timestamp in seconds with double precision.
+    /// The delta time is checked against `dt_policy` and the returned status reports which policy was applied.
+    pub fn update_no_mag_secs_f64(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, timestamp: f64) -> FusionDtStatus {
+        let (delta_t, status) = self.advance_timestamp(FusionTimestamp::Seconds(timestamp));
+        if let Some(delta_t) = delta_t {
+            self.update_no_mag_by_duration_seconds(gyr, acc, delta_t);
+        }
+        status
     }
 
     /// Updates the AHRS algorithm based on gyroscope data in degrees/s, acceleration data in g force and magnetic measurements in degrees.
     ///
-    /// The time is provided using an absolute timestamp in seconds since the first measurement.
-    /// Note that if you provide unix timestamps, the precision of f32 will not be enough to correctly compute the time difference.
+    /// The time is provided using an absolute timestamp in microseconds.
+    /// The delta time is checked against `dt_policy` and the returned status reports which policy was applied.
+    /// The time difference is computed with integer arithmetic, so it does not lose precision after long uptimes.
     ///
     /// # Examples
     /// ```no_run
     /// use imu_fusion::{Fusion, FusionAhrsSettings, FusionVector};
+    /// use std::time::Instant;
     ///
     /// const SAMPLE_RATE_HZ: u32 = 100;
     ///
     /// let ahrs_settings = FusionAhrsSettings::new();
     /// let mut fusion = Fusion::new(SAMPLE_RATE_HZ, ahrs_settings);
-    /// let mut current_ts = 0f32;
+    /// let start = Instant::now();
     ///
     /// loop {
     ///     let gyr = FusionVector::new(0f32, 0f32, 0f32); // replace this with actual gyroscope data in degrees/s
     ///     let acc = FusionVector::new(0f32, 0f32, 1.0f32); // replace this with actual accelerometer data in g
     ///     let mag = FusionVector::new(0f32, 0f32, 1.0f32); // replace this with actual magnetic measurement in degrees
-    ///     fusion.update(gyr, acc, mag, current_ts);
+    ///     fusion.update_micros(gyr, acc, mag, start.elapsed().as_micros() as u64);
+    /// }
+    ///
+    /// ```
+    pub fn update_micros(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, mag: FusionVector<T>, timestamp_us: u64) -> FusionDtStatus {
+        let (delta_t, status) = self.advance_timestamp(FusionTimestamp::Microseconds(timestamp_us));
+        if let Some(delta_t) = delta_t {
+            self.update_by_duration_seconds(gyr, acc, mag, delta_t);
+        }
+        status
+    }
+
+    /// Updates the AHRS algorithm based on gyroscope data in degrees/s, acceleration data in g force and magnetic measurements in degrees.
+    ///
+    /// The time is provided using a 32-bit microsecond tick counter, as found on most microcontrollers.
+    /// The delta time is checked against `dt_policy` and the returned status reports which policy was applied.
+    /// The counter may wrap around, but the time between two updates must be less than 2^31 microseconds (~35.8 minutes),
+    /// a longer gap is taken as the counter going backwards.
+    /// After updates timestamped in seconds, the counter is taken to be in the same time base, modulo 2^32 microseconds.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use imu_fusion::{Fusion, FusionAhrsSettings, FusionVector};
+    ///
+    /// const SAMPLE_RATE_HZ: u32 = 100;
+    ///
+    /// let ahrs_settings = FusionAhrsSettings::new();
+    /// let mut fusion = Fusion::new(SAMPLE_RATE_HZ, ahrs_settings);
+    /// let mut ticks_us = 0u32;
+    ///
+    /// loop {
+    ///     let gyr = FusionVector::new(0f32, 0f32, 0f32); // replace this with actual gyroscope data in degrees/s
+    ///     let acc = FusionVector::new(0f32, 0f32, 1.0f32); // replace this with actual accelerometer data in g
+    ///     let mag = FusionVector::new(0f32, 0f32, 1.0f32); // replace this with actual magnetic measurement in degrees
+    ///     fusion.update_micros_u32(gyr, acc, mag, ticks_us);
+    ///     ticks_us = ticks_us.wrapping_add(1_000_000 / SAMPLE_RATE_HZ); // replace this with actual timer ticks
+    /// }
+    ///
+    /// ```
+    pub fn update_micros_u32(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, mag: FusionVector<T>, timestamp_us: u32) -> FusionDtStatus {
+        let timestamp = self.following_micros_u32(timestamp_us);
+        let (delta_t, status) = self.advance_timestamp(timestamp);
+        if let Some(delta_t) = delta_t {
+            self.update_by_duration_seconds(gyr, acc, mag, delta_t);
+        }
+        status
+    }
+
+    /// Updates the AHRS algorithm based on gyroscope data in degrees/s, acceleration data in g force and magnetic measurements in degrees.
+    ///
+    /// The time is provided using an absolute timestamp in seconds with double precision, which is enough for unix timestamps.
+    /// The delta time is checked against `dt_policy` and the returned status reports which policy was applied.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use imu_fusion::{Fusion, FusionAhrsSettings, FusionVector};
+    /// use std::time::{SystemTime, UNIX_EPOCH};
+    ///
+    /// const SAMPLE_RATE_HZ: u32 = 100;
+    ///
+    /// let ahrs_settings = FusionAhrsSettings::new();
+    /// let mut fusion = Fusion::new(SAMPLE_RATE_HZ, ahrs_settings);
+    ///
+    /// loop {
+    ///     let gyr = FusionVector::new(0f32, 0f32, 0f32); // replace this with actual gyroscope data in degrees/s
+    ///     let acc = FusionVector::new(0f32, 0f32, 1.0f32); // replace this with actual accelerometer data in g
+    ///     let mag = FusionVector::new(0f32, 0f32, 1.0f32); // replace this with actual magnetic measurement in degrees
+    ///     let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64();
+    ///     fusion.update_secs_f64(gyr, acc, mag, timestamp);
+    /// }
+    ///
+    /// ```
+    pub fn update_secs_f64(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, mag: FusionVector<T>, timestamp: f64) -> FusionDtStatus {
+        let (delta_t, status) = self.advance_timestamp(FusionTimestamp::Seconds(timestamp));
+        if let Some(delta_t) = delta_t {
+            self.update_by_duration_seconds(gyr, acc, mag, delta_t);
+        }
+        status
+    }
+
+    /// Same as `update_no_mag`, but rejects non-finite inputs and restores the previous state if the
+    /// quaternion becomes non-finite.
+    pub fn try_update_no_mag(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, timestamp: T) -> Result<FusionDtStatus, FusionError>
+    where
+        F: Clone,
+    {
+        if !timestamp.is_finite() {
+            return Err(FusionError::NonFiniteTimestamp);
+        }
+        check_inputs(gyr, acc, FusionVector::zero(), T::ZERO)?;
+        self.try_advance_timestamp(FusionTimestamp::Seconds(timestamp.to_f64()), |fusion, delta_t| fusion.try_update_no_mag_by_duration_seconds(gyr, acc, delta_t))
+    }
+
+    /// Same as `update_no_mag_by_duration_seconds`, but rejects non-finite inputs and restores the previous state if the
+    /// quaternion becomes non-finite.
+    pub fn try_update_no_mag_by_duration_seconds(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, delta_t: T) -> Result<(), FusionError>
+    where
+        F: Clone,
+    {
+        check_inputs(gyr, acc, FusionVector::zero(), delta_t)?;
+        let (ahrs, offset, temp_offset) = (self.ahrs.clone(), self.offset.clone(), self.temp_offset.clone());
+        self.update_no_mag_by_duration_seconds(gyr, acc, delta_t);
+        self.check_quaternion(ahrs, offset, temp_offset)
+    }
+
+    /// Same as `update`, but rejects non-finite inputs and restores the previous state if the
+    /// quaternion becomes non-finite.
+    ///
+    /// # Examples
+    /// ```
+    /// use imu_fusion::{Fusion, FusionAhrsSettings, FusionError, FusionVector};
+    ///
+    /// const SAMPLE_RATE_HZ: u32 = 100;
+    ///
+    /// let ahrs_settings = FusionAhrsSettings::new();
+    /// let mut fusion = Fusion::new(SAMPLE_RATE_HZ, ahrs_settings);
+    ///
+    /// let gyr = FusionVector::new(f32::NAN, 0f32, 0f32); // e.g. a corrupted gyroscope read
+    /// let acc = FusionVector::new(0f32, 0f32, 1.0f32);
+    /// let mag = FusionVector::new(1.0f32, 0f32, 0f32);
+    /// assert_eq!(fusion.try_update(gyr, acc, mag, 0.01f32), Err(FusionError::NonFiniteGyroscope));
+    /// ```
+    pub fn try_update(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, mag: FusionVector<T>, timestamp: T) -> Result<FusionDtStatus, FusionError>
+    where
+        F: Clone,
+    {
+        if !timestamp.is_finite() {
+            return Err(FusionError::NonFiniteTimestamp);
+        }
+        check_inputs(gyr, acc, mag, T::ZERO)?;
+        self.try_advance_timestamp(FusionTimestamp::Seconds(timestamp.to_f64()), |fusion, delta_t| fusion.try_update_by_duration_seconds(gyr, acc, mag, delta_t))
+    }
+
+    /// Same as `update_by_duration_seconds`, but rejects non-finite inputs and restores the previous state if the
+    /// quaternion becomes non-finite.
+    pub fn try_update_by_duration_seconds(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, mag: FusionVector<T>, delta_t: T) -> Result<(), FusionError>
+    where
+        F: Clone,
+    {
+        check_inputs(gyr, acc, mag, delta_t)?;
+        let (ahrs, offset, temp_offset) = (self.ahrs.clone(), self.offset.clone(), self.temp_offset.clone());
+        self.update_by_duration_seconds(gyr, acc, mag, delta_t);
+        self.check_quaternion(ahrs, offset, temp_offset)
+    }
+
+    /// Same as `update_no_mag_micros`, but rejects non-finite inputs and restores the previous state if the
+    /// quaternion becomes non-finite.
+    pub fn try_update_no_mag_micros(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, timestamp_us: u64) -> Result<FusionDtStatus, FusionError>
+    where
+        F: Clone,
+    {
+        check_inputs(gyr, acc, FusionVector::zero(), T::ZERO)?;
+        self.try_advance_timestamp(FusionTimestamp::Microseconds(timestamp_us), |fusion, delta_t| fusion.try_update_no_mag_by_duration_seconds(gyr, acc, delta_t))
+    }
+
+    /// Same as `update_no_mag_micros_u32`, but rejects non-finite inputs and restores the previous state if the
+    /// quaternion becomes non-finite.
+    pub fn try_update_no_mag_micros_u32(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, timestamp_us: u32) -> Result<FusionDtStatus, FusionError>
+    where
+        F: Clone,
+    {
+        check_inputs(gyr, acc, FusionVector::zero(), T::ZERO)?;
+        let timestamp = self.following_micros_u32(timestamp_us);
+        self.try_advance_timestamp(timestamp, |fusion, delta_t| fusion.try_update_no_mag_by_duration_seconds(gyr, acc, delta_t))
+    }
+
+    /// Same as `update_no_mag_secs_f64`, but rejects non-finite inputs and restores the previous state if the
+    /// quaternion becomes non-finite.
+    pub fn try_update_no_mag_secs_f64(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, timestamp: f64) -> Result<FusionDtStatus, FusionError>
+    where
+        F: Clone,
+    {
+        if !timestamp.is_finite() {
+            return Err(FusionError::NonFiniteTimestamp);
+        }
+        check_inputs(gyr, acc, FusionVector::zero(), T::ZERO)?;
+        self.try_advance_timestamp(FusionTimestamp::Seconds(timestamp), |fusion, delta_t| fusion.try_update_no_mag_by_duration_seconds(gyr, acc, delta_t))
+    }
+
+    /// Same as `update_micros`, but rejects non-finite inputs and restores the previous state if the
+    /// quaternion becomes non-finite.
+    pub fn try_update_micros(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, mag: FusionVector<T>, timestamp_us: u64) -> Result<FusionDtStatus, FusionError>
+    where
+        F: Clone,
+    {
+        check_inputs(gyr, acc, mag, T::ZERO)?;
+        self.try_advance_timestamp(FusionTimestamp::Microseconds(timestamp_us), |fusion, delta_t| fusion.try_update_by_duration_seconds(gyr, acc, mag, delta_t))
+    }
+
+    /// Same as `update_micros_u32`, but rejects non-finite inputs and restores the previous state if the
+    /// quaternion becomes non-finite.
+    pub fn try_update_micros_u32(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, mag: FusionVector<T>, timestamp_us: u32) -> Result<FusionDtStatus, FusionError>
+    where
+        F: Clone,
+    {
+        check_inputs(gyr, acc, mag, T::ZERO)?;
+        let timestamp = self.following_micros_u32(timestamp_us);
+        self.try_advance_timestamp(timestamp, |fusion, delta_t| fusion.try_update_by_duration_seconds(gyr, acc, mag, delta_t))
+    }
+
+    /// Same as `update_secs_f64`, but rejects non-finite inputs and restores the previous state if the
+    /// quaternion becomes non-finite.
+    pub fn try_update_secs_f64(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, mag: FusionVector<T>, timestamp: f64) -> Result<FusionDtStatus, FusionError>
+    where
+        F: Clone,
+    {
+        if !timestamp.is_finite() {
+            return Err(FusionError::NonFiniteTimestamp);
+        }
+        check_inputs(gyr, acc, mag, T::ZERO)?;
+        self.try_advance_timestamp(FusionTimestamp::Seconds(timestamp), |fusion, delta_t| fusion.try_update_by_duration_seconds(gyr, acc, mag, delta_t))
+    }
+
+    fn check_quaternion(&mut self, ahrs: F, offset: FusionGyrOffset<T>, temp_offset: Option<FusionGyrTempOffset<T>>) -> Result<(), FusionError> {
+        if self.ahrs.quaternion().is_finite() {
+            Ok(())
+        } else {
+            self.ahrs = ahrs;
+            self.offset = offset;
+            self.temp_offset = temp_offset;
+            Err(FusionError::NonFiniteQuaternion)
+        }
+    }
+
+    /// Extends a 32-bit microsecond tick counter relative to the last timestamp, the first sample is counted from zero.
+    fn following_micros_u32(&self, timestamp_us: u32) -> FusionTimestamp {
+        match self.last_timestamp {
+            Some(last) => last.following_micros_u32(timestamp_us),
+            None => FusionTimestamp::Microseconds(timestamp_us as u64),
+        }
+    }
+
+    /// Corrects the gyroscope with the temperature-compensated offset if set, otherwise with the built-in offset.
+    fn update_gyr_offset(&mut self, gyr: FusionVector<T>) -> FusionVector<T> {
+        match &mut self.temp_offset {
+            Some(temp_offset) => temp_offset.update(gyr, self.temperature),
+            None => self.offset.update(gyr),
+        }
+    }
+
+    fn advance_timestamp(&mut self, timestamp: FusionTimestamp) -> (Option<T>, FusionDtStatus) {
+        // Time before the first sample is measured from zero
+        let first_sample = self.last_timestamp.is_none();
+        let delta_t = timestamp.seconds_since(self.last_timestamp.unwrap_or(FusionTimestamp::Seconds(0.0f64)));
+        let nominal_dt = T::ONE / T::from_f64(self.sample_rate as f64);
+        let (delta_t, status) = self.dt_policy.apply(T::from_f64(delta_t), first_sample, nominal_dt);
+        // A rejected sample must not move the reference the next delta time is measured from
+        if status != FusionDtStatus::NonMonotonicRejected {
+            self.last_timestamp = Some(timestamp);
+        }
+        (delta_t, status)
+    }
+
+    /// Advances the timestamp and runs the fallible update, the timestamp is restored if the update fails.
+    fn try_advance_timestamp(&mut self, timestamp: FusionTimestamp, update: impl FnOnce(&mut Self, T) -> Result<(), FusionError>) -> Result<FusionDtStatus, FusionError> {
+        let last_timestamp = self.last_timestamp;
+        let (delta_t, status) = self.advance_timestamp(timestamp);
+        if let Some(delta_t) = delta_t {
+            update(self, delta_t).inspect_err(|_| self.last_timestamp = last_timestamp)?;
+        }
+        Ok(status)
+    }
+
+    /// Sets the gyroscope temperature in degrees Celsius used by `temp_offset` for the following updates.
+    ///
+    /// Setting `temp_offset` replaces the built-in `offset` stage, so the offset is not corrected twice. To disable
+    /// offset correction altogether, leave `temp_offset` unset and give `offset` a threshold of zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use imu_fusion::{Fusion, FusionAhrsSettings, FusionGyrTempOffset, FusionVector};
+    ///
+    /// const SAMPLE_RATE_HZ: u32 = 100;
+    ///
+    /// let mut fusion = Fusion::new(SAMPLE_RATE_HZ, FusionAhrsSettings::new());
+    /// fusion.temp_offset = Some(FusionGyrTempOffset::new(SAMPLE_RATE_HZ));
+    ///
+    /// let gyr = FusionVector::new(0f32, 0f32, 0f32); // replace this with actual gyroscope data in degrees/s
+    /// let acc = FusionVector::new(0f32, 0f32, 1.0f32); // replace this with actual accelerometer data in g
+    /// fusion.set_temperature(21.5f32); // replace this with the actual gyroscope temperature
+    /// fusion.update_no_mag(gyr, acc, 0.01f32);
+    /// ```
+    pub fn set_temperature(&mut self, temperature: T) {
+        self.temperature = temperature;
+    }
+
+    /// Sets the AHRS orientation.
+    pub fn set_quaternion(&mut self, quaternion: FusionQuaternion<T>) {
+        self.ahrs.set_quaternion(quaternion);
+    }
+
+    /// Sets the AHRS orientation from euler angles in degrees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use imu_fusion::{Angle, Fusion, FusionAhrsSettings, FusionEuler};
+    ///
+    /// const SAMPLE_RATE_HZ: u32 = 100;
+    ///
+    /// let ahrs_settings = FusionAhrsSettings::new();
+    /// let mut fusion = Fusion::new(SAMPLE_RATE_HZ, ahrs_settings);
+    ///
+    /// fusion.set_euler(FusionEuler { angle: Angle { roll: 0f32, pitch: 0f32, yaw: 90.0f32 } });
+    /// ```
+    pub fn set_euler(&mut self, euler: FusionEuler<T>) {
+        self.ahrs.set_quaternion(FusionQuaternion::from_euler(euler));
+    }
+
+    /// Obtain euler angle current sensor position
+    ///
+    /// Euler angles are provided in degrees
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use imu_fusion::{Fusion, FusionAhrsSettings};
+    ///
+    /// const SAMPLE_RATE_HZ: u32 = 100;
+    ///
+    /// let ahrs_settings = FusionAhrsSettings::new();
+    /// let mut fusion = Fusion::new(SAMPLE_RATE_HZ, ahrs_settings);
+    ///
+    /// // ...update sensor values
+    ///
+    /// let euler = fusion.euler();
+    /// println!("Roll {}, Pitch {}, Yaw {}", euler.angle.roll, euler.angle.pitch, euler.angle.yaw);
+    /// ```
+    pub fn euler(&self) -> FusionEuler<T> {
+        self.ahrs.quaternion().euler()
+    }
+
+    pub fn quaternion(&self) -> FusionQuaternion<T> {
+        self.ahrs.quaternion()
+    }
+
+    /// Obtain flags of the AHRS algorithm
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use imu_fusion::{Fusion, FusionAhrsSettings};
+    ///
+    /// const SAMPLE_RATE_HZ: u32 = 100;
+    ///
+    /// let ahrs_settings = FusionAhrsSettings::new();
+    /// let mut fusion = Fusion::new(SAMPLE_RATE_HZ, ahrs_settings);
+    ///
+    /// // ...update sensor values
+    ///
+    /// let flags = fusion.flags();
+    /// println!("Initialising {}, Acceleration recovery {}", flags.initializing, flags.acceleration_recovery);
+    /// ```
+    pub fn flags(&self) -> FusionAhrsFlags {
+        self.ahrs.flags()
+    }
+
+    /// Obtain the underlying orientation filter
+    pub fn internal_ahrs(&self) -> &F {
+        &self.ahrs
+    }
+}
+
+impl<T: FusionFloat> Fusion<T> {
+    pub fn new(sample_rate: u32, ahrs_settings: FusionAhrsSettings<T>) -> Self {
+        let mut ahrs = FusionAhrs::default();
+        ahrs.update_settings(ahrs_settings);
+        Self::with_filter(sample_rate, ahrs)
+    }
+
+    /// Updates the AHRS algorithm based on gyroscope data in degrees/s, acceleration data in g force and a heading in degrees.
+    ///
+    /// The time is provided using an absolute timestamp in seconds since the first measurement.
+    /// The delta time is checked against `dt_policy` and the returned status reports which policy was applied.
+    /// Note that if you provide unix timestamps, the precision of f32 will not be enough to correctly compute the time difference,
+    /// use the `_micros` or `_secs_f64` variants instead.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use imu_fusion::{Fusion, FusionVector, FusionAhrsSettings};
+    ///
+    /// const SAMPLE_RATE_HZ: u32 = 100;
+    ///
+    /// let ahrs_settings = FusionAhrsSettings::new();
+    /// let mut fusion = Fusion::new(SAMPLE_RATE_HZ, ahrs_settings);
+    /// let mut current_ts = 0f32;
+    ///
+    /// loop {
+    ///     let gyr = FusionVector::new(0f32, 0f32, 0f32); // replace this with actual gyroscope data in degrees/s
+    ///     let acc = FusionVector::new(0f32, 0f32, 1.0f32); // replace this with actual accelerometer data in g
+    ///     let external_heading = 0f32; // replace this with actual heading in degrees
+    ///     fusion.update_external_heading(gyr, acc, external_heading, current_ts);
     ///     current_ts += 1.0 / SAMPLE_RATE_HZ as f32
     /// }
     ///
     /// ```
-    pub fn update(
+    pub fn update_external_heading(
         &mut self,
-        gyr: FusionVector,
-        acc: FusionVector,
-        mag: FusionVector,
-        timestamp: f32,
-    ) {
-        let delta_t = timestamp - self.last_timestamp;
-        self.update_by_duration_seconds(gyr, acc, mag, delta_t);
-        self.last_timestamp = timestamp;
+        gyr: FusionVector<T>,
+        acc: FusionVector<T>,
+        heading: T,
+        timestamp: T,
+    ) -> FusionDtStatus {
+        let (delta_t, status) = self.advance_timestamp(FusionTimestamp::Seconds(timestamp.to_f64()));
+        if let Some(delta_t) = delta_t {
+            self.update_external_heading_by_duration_seconds(gyr, acc, heading, delta_t);
+        }
+        status
     }
 
-    /// Updates the AHRS algorithm based on gyroscope data in degrees/s, acceleration data in g force and magnetic measurements in degrees.
+    /// Updates the AHRS algorithm based on gyroscope data in degrees/s, acceleration data in g force and a heading in degrees.
     ///
     /// The time is provided using a duration in seconds since the last measurement.
     /// Note that this won't increase the internal timestamp and using the timestamp version of update functions will produce incorrect results.
+    ///
     /// # Examples
     /// ```no_run
-    /// use imu_fusion::{Fusion, FusionAhrsSettings, FusionVector};
+    /// use imu_fusion::{Fusion, FusionVector, FusionAhrsSettings};
     /// use std::time::{Duration, Instant};
     ///
     /// const SAMPLE_RATE_HZ: u32 = 100;
@@ -223,51 +698,170 @@ impl Fusion {
     /// loop {
     ///     let gyr = FusionVector::new(0f32, 0f32, 0f32); // replace this with actual gyroscope data in degrees/s
     ///     let acc = FusionVector::new(0f32, 0f32, 1.0f32); // replace this with actual accelerometer data in g
-    ///     let mag = FusionVector::new(0f32, 0f32, 1.0f32); // replace this with actual magnetic measurement in degrees
-    ///     fusion.update_by_duration_seconds(gyr, acc, mag, last_ts.elapsed().as_secs_f32());
+    ///     let external_heading = 0f32; // replace this with actual heading in degrees
+    ///     fusion.update_external_heading(gyr, acc, external_heading, last_ts.elapsed().as_secs_f32());
     ///     last_ts = Instant::now();
     /// }
     ///
     /// ```
-    pub fn update_by_duration_seconds(
+    pub fn update_external_heading_by_duration_seconds(
         &mut self,
-        gyr: FusionVector,
-        acc: FusionVector,
-        mag: FusionVector,
-        delta_t: f32,
+        gyr: FusionVector<T>,
+        acc: FusionVector<T>,
+        heading: T,
+        delta_t: T,
     ) {
+        // Apply axes alignment
+        let gyr = swap(gyr, self.gyr_alignment);
+        let acc = swap(acc, self.acc_alignment);
+
         // Apply calibration
         let mut gyr = self.inertial_calibration(gyr, self.gyr_misalignment, self.gyr_sensitivity, self.gyr_offset);
         let acc = self.inertial_calibration(acc, self.acc_misalignment, self.acc_sensitivity, self.acc_offset);
-        let mag = self.magnetic_calibration(mag, self.soft_iron_matrix, self.hard_iron_offset);
 
         // Update gyroscope offset correction algorithm
-        gyr = self.offset.update(gyr);
+        gyr = self.update_gyr_offset(gyr);
 
-        self.ahrs.update(gyr, acc, mag, delta_t);
+        self.ahrs.update_external_heading(gyr, acc, heading, delta_t);
     }
 
-    /// Obtain euler angle current sensor position
+    /// Updates the AHRS algorithm based on gyroscope data in degrees/s, acceleration data in g force and a heading in degrees.
     ///
-    /// Euler angles are provided in degrees
+    /// The time is provided using an absolute timestamp in microseconds.
+    /// The delta time is checked against `dt_policy` and the returned status reports which policy was applied.
+    pub fn update_external_heading_micros(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, heading: T, timestamp_us: u64) -> FusionDtStatus {
+        let (delta_t, status) = self.advance_timestamp(FusionTimestamp::Microseconds(timestamp_us));
+        if let Some(delta_t) = delta_t {
+            self.update_external_heading_by_duration_seconds(gyr, acc, heading, delta_t);
+        }
+        status
+    }
+
+    /// Updates the AHRS algorithm based on gyroscope data in degrees/s, acceleration data in g force and a heading in degrees.
+    ///
+    /// The time is provided using a wrapping 32-bit tick counter in microseconds, see `update_micros_u32`.
+    /// The delta time is checked against `dt_policy` and the returned status reports which policy was applied.
+    pub fn update_external_heading_micros_u32(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, heading: T, timestamp_us: u32) -> FusionDtStatus {
+        let timestamp = self.following_micros_u32(timestamp_us);
+        let (delta_t, status) = self.advance_timestamp(timestamp);
+        if let Some(delta_t) = delta_t {
+            self.update_external_heading_by_duration_seconds(gyr, acc, heading, delta_t);
+        }
+        status
+    }
+
+    /// Updates the AHRS algorithm based on gyroscope data in degrees/s, acceleration data in g force and a heading in degrees.
+    ///
+    /// The time is provided using an absolute timestamp in seconds with double precision.
+    /// The delta time is checked against `dt_policy` and the returned status reports which policy was applied.
+    pub fn update_external_heading_secs_f64(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, heading: T, timestamp: f64) -> FusionDtStatus {
+        let (delta_t, status) = self.advance_timestamp(FusionTimestamp::Seconds(timestamp));
+        if let Some(delta_t) = delta_t {
+            self.update_external_heading_by_duration_seconds(gyr, acc, heading, delta_t);
+        }
+        status
+    }
+
+    /// Same as `update_external_heading`, but rejects non-finite inputs and restores the previous state if the
+    /// quaternion becomes non-finite.
+    pub fn try_update_external_heading(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, heading: T, timestamp: T) -> Result<FusionDtStatus, FusionError> {
+        if !timestamp.is_finite() {
+            return Err(FusionError::NonFiniteTimestamp);
+        }
+        check_inputs(gyr, acc, FusionVector::zero(), T::ZERO)?;
+        if !heading.is_finite() {
+            return Err(FusionError::NonFiniteHeading);
+        }
+        self.try_advance_timestamp(FusionTimestamp::Seconds(timestamp.to_f64()), |fusion, delta_t| {
+            fusion.try_update_external_heading_by_duration_seconds(gyr, acc, heading, delta_t)
+        })
+    }
+
+    /// Same as `update_external_heading_by_duration_seconds`, but rejects non-finite inputs and restores the previous state if the
+    /// quaternion becomes non-finite.
+    pub fn try_update_external_heading_by_duration_seconds(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, heading: T, delta_t: T) -> Result<(), FusionError> {
+        check_inputs(gyr, acc, FusionVector::zero(), delta_t)?;
+        if !heading.is_finite() {
+            return Err(FusionError::NonFiniteHeading);
+        }
+        let (ahrs, offset, temp_offset) = (self.ahrs.clone(), self.offset.clone(), self.temp_offset.clone());
+        self.update_external_heading_by_duration_seconds(gyr, acc, heading, delta_t);
+        self.check_quaternion(ahrs, offset, temp_offset)
+    }
+
+    /// Same as `update_external_heading_micros`, but rejects non-finite inputs and restores the previous state if the
+    /// quaternion becomes non-finite.
+    pub fn try_update_external_heading_micros(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, heading: T, timestamp_us: u64) -> Result<FusionDtStatus, FusionError> {
+        check_inputs(gyr, acc, FusionVector::zero(), T::ZERO)?;
+        if !heading.is_finite() {
+            return Err(FusionError::NonFiniteHeading);
+        }
+        self.try_advance_timestamp(FusionTimestamp::Microseconds(timestamp_us), |fusion, delta_t| {
+            fusion.try_update_external_heading_by_duration_seconds(gyr, acc, heading, delta_t)
+        })
+    }
+
+    /// Same as `update_external_heading_micros_u32`, but rejects non-finite inputs and restores the previous state if the
+    /// quaternion becomes non-finite.
+    pub fn try_update_external_heading_micros_u32(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, heading: T, timestamp_us: u32) -> Result<FusionDtStatus, FusionError> {
+        check_inputs(gyr, acc, FusionVector::zero(), T::ZERO)?;
+        if !heading.is_finite() {
+            return Err(FusionError::NonFiniteHeading);
+        }
+        let timestamp = self.following_micros_u32(timestamp_us);
+        self.try_advance_timestamp(timestamp, |fusion, delta_t| fusion.try_update_external_heading_by_duration_seconds(gyr, acc, heading, delta_t))
+    }
+
+    /// Same as `update_external_heading_secs_f64`, but rejects non-finite inputs and restores the previous state if the
+    /// quaternion becomes non-finite.
+    pub fn try_update_external_heading_secs_f64(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, heading: T, timestamp: f64) -> Result<FusionDtStatus, FusionError> {
+        if !timestamp.is_finite() {
+            return Err(FusionError::NonFiniteTimestamp);
+        }
+        check_inputs(gyr, acc, FusionVector::zero(), T::ZERO)?;
+        if !heading.is_finite() {
+            return Err(FusionError::NonFiniteHeading);
+        }
+        self.try_advance_timestamp(FusionTimestamp::Seconds(timestamp), |fusion, delta_t| {
+            fusion.try_update_external_heading_by_duration_seconds(gyr, acc, heading, delta_t)
+        })
+    }
+
+    /// Initialises the AHRS orientation from accelerometer data in g and magnetometer data in arbitrary units.
+    ///
+    /// Axes alignment and calibration are applied to the measurements and the initialisation gain ramp is skipped,
+    /// unless the accelerometer measurement is zero.
+    /// Pass a zero magnetometer vector to initialise with a heading of zero.
     ///
     /// # Examples
     ///
     /// ```
-    /// use imu_fusion::{Fusion, FusionAhrsSettings};
+    /// use imu_fusion::{Fusion, FusionAhrsSettings, FusionVector};
     ///
     /// const SAMPLE_RATE_HZ: u32 = 100;
     ///
     /// let ahrs_settings = FusionAhrsSettings::new();
     /// let mut fusion = Fusion::new(SAMPLE_RATE_HZ, ahrs_settings);
     ///
-    /// // ...update sensor values
-    ///
-    /// let euler = fusion.euler();
-    /// println!("Roll {}, Pitch {}, Yaw {}", euler.angle.roll, euler.angle.pitch, euler.angle.yaw);
+    /// let acc = FusionVector::new(0f32, 0f32, 1.0f32); // replace this with actual accelerometer data in g
+    /// let mag = FusionVector::new(1.0f32, 0f32, 0f32); // replace this with actual magnetometer data in arbitrary units
+    /// fusion.initialise_from(acc, mag);
     /// ```
-    pub fn euler(&self) -> FusionEuler {
-        self.ahrs.quaternion.euler()
+    pub fn initialise_from(&mut self, acc: FusionVector<T>, mag: FusionVector<T>) {
+        // Apply axes alignment
+        let acc = swap(acc, self.acc_alignment);
+        let mag = swap(mag, self.mag_alignment);
+
+        // Apply calibration
+        let acc = self.inertial_calibration(acc, self.acc_misalignment, self.acc_sensitivity, self.acc_offset);
+        let mag = if mag.is_zero() { mag } else { self.magnetic_calibration(mag, self.soft_iron_matrix, self.hard_iron_offset) };
+
+        self.ahrs.initialise_from(acc, mag);
+    }
+
+    /// Sets the heading of the AHRS orientation in degrees.
+    pub fn set_heading(&mut self, heading: T) {
+        self.ahrs.set_heading(heading);
     }
 
     /// Obtain acceleration of sensor in earth's frame of reference
@@ -289,11 +883,212 @@ impl Fusion {
     /// let acc = fusion.earth_acc();
     /// println!("x {}, y {}, z {}", acc.x, acc.y, acc.z);
     /// ```
-    pub fn earth_acc(&self) -> FusionVector {
+    pub fn earth_acc(&self) -> FusionVector<T> {
         self.ahrs.earth_acc()
     }
 
-    pub fn quaternion(&self) -> FusionQuaternion {
-        self.ahrs.quaternion
+    /// Obtain linear acceleration of sensor in sensor's frame of reference
+    ///
+    /// The values returned are provided in g force with gravity removed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use imu_fusion::{Fusion, FusionAhrsSettings};
+    ///
+    /// const SAMPLE_RATE_HZ: u32 = 100;
+    ///
+    /// let ahrs_settings = FusionAhrsSettings::new();
+    /// let mut fusion = Fusion::new(SAMPLE_RATE_HZ, ahrs_settings);
+    ///
+    /// // ...update sensor values
+    ///
+    /// let acc = fusion.linear_acc();
+    /// println!("x {}, y {}, z {}", acc.x, acc.y, acc.z);
+    /// ```
+    pub fn linear_acc(&self) -> FusionVector<T> {
+        self.ahrs.linear_acc()
+    }
+
+    /// Obtain direction of gravity in sensor's frame of reference
+    ///
+    /// The values returned are provided in g force, as measured by a stationary accelerometer in the configured convention
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use imu_fusion::{Fusion, FusionAhrsSettings};
+    ///
+    /// const SAMPLE_RATE_HZ: u32 = 100;
+    ///
+    /// let ahrs_settings = FusionAhrsSettings::new();
+    /// let mut fusion = Fusion::new(SAMPLE_RATE_HZ, ahrs_settings);
+    ///
+    /// // ...update sensor values
+    ///
+    /// let gravity = fusion.gravity();
+    /// println!("x {}, y {}, z {}", gravity.x, gravity.y, gravity.z);
+    /// ```
+    pub fn gravity(&self) -> FusionVector<T> {
+        self.ahrs.gravity()
+    }
+
+    /// Obtain internal states of the AHRS algorithm
+    ///
+    /// Acceleration and magnetic errors are provided in degrees, recovery triggers as a ratio from 0 to 1
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use imu_fusion::{Fusion, FusionAhrsSettings};
+    ///
+    /// const SAMPLE_RATE_HZ: u32 = 100;
+    ///
+    /// let ahrs_settings = FusionAhrsSettings::new();
+    /// let mut fusion = Fusion::new(SAMPLE_RATE_HZ, ahrs_settings);
+    ///
+    /// // ...update sensor values
+    ///
+    /// let states = fusion.internal_states();
+    /// println!("Acceleration error {}, Magnetic error {}", states.acceleration_error, states.magnetic_error);
+    /// ```
+    pub fn internal_states(&self) -> FusionAhrsInternalStates<T> {
+        self.ahrs.internal_states()
+    }
+}
+
+#[test]
+fn dt_policy_test() {
+    let mut fusion = Fusion::new(100, FusionAhrsSettings::new());
+    fusion.dt_policy.skip_first_sample = true;
+    fusion.dt_policy.max_dt = 0.1f32;
+    fusion.dt_policy.reject_non_monotonic = true;
+    let gyr = FusionVector::new(0.0f32, 0.0f32, 90.0f32);
+    let acc = FusionVector::new(0.0f32, 0.0f32, 1.0f32);
+    assert_eq!(fusion.update_no_mag_micros(gyr, acc, 1_000_000_000u64), FusionDtStatus::FirstSampleSkipped);
+    assert_eq!(fusion.quaternion().w, 1.0f32);
+    assert_eq!(fusion.update_no_mag_micros(gyr, acc, 1_000_010_000u64), FusionDtStatus::Measured);
+    assert_eq!(fusion.update_no_mag_micros(gyr, acc, 1_000_010_000u64), FusionDtStatus::NonMonotonicRejected);
+    assert_eq!(fusion.update_no_mag_micros(gyr, acc, 1_005_000_000u64), FusionDtStatus::Clamped);
+}
+
+#[test]
+fn dt_policy_rejected_timestamp_test() {
+    let mut fusion = Fusion::new(100, FusionAhrsSettings::new());
+    fusion.dt_policy.max_dt = 0.1f32;
+    fusion.dt_policy.reject_non_monotonic = true;
+    let gyr = FusionVector::new(0.0f32, 0.0f32, 90.0f32);
+    let acc = FusionVector::new(0.0f32, 0.0f32, 1.0f32);
+    assert_eq!(fusion.update_no_mag_micros(gyr, acc, 1_000_000_000u64), FusionDtStatus::Clamped);
+    assert_eq!(fusion.update_no_mag_micros(gyr, acc, 500_000_000u64), FusionDtStatus::NonMonotonicRejected);
+    // Measured from the last accepted sample rather than the rejected one
+    assert_eq!(fusion.update_no_mag_micros(gyr, acc, 1_000_010_000u64), FusionDtStatus::Measured);
+    assert!(matches!(fusion.last_timestamp, Some(FusionTimestamp::Microseconds(1_000_010_000u64))));
+}
+
+#[test]
+fn try_update_test() {
+    let mut fusion = Fusion::new(100, FusionAhrsSettings::new());
+    let gyr = FusionVector::new(0.0f32, 0.0f32, 0.0f32);
+    let acc = FusionVector::new(0.0f32, 0.0f32, 1.0f32);
+    assert_eq!(fusion.try_update_no_mag(gyr, acc, 0.01f32), Ok(FusionDtStatus::Measured));
+    assert_eq!(fusion.try_update_no_mag(gyr, acc, f32::INFINITY), Err(FusionError::NonFiniteTimestamp));
+    assert_eq!(fusion.try_update_no_mag(FusionVector::new(f32::MAX, f32::MAX, f32::MAX), acc, 1e30f32), Err(FusionError::NonFiniteQuaternion));
+    assert!(fusion.quaternion().is_finite());
+    assert!(matches!(fusion.last_timestamp, Some(FusionTimestamp::Seconds(timestamp)) if timestamp == 0.01f32 as f64));
+}
+
+#[test]
+fn try_update_timestamp_variants_test() {
+    let mut fusion = Fusion::new(100, FusionAhrsSettings::new());
+    let gyr = FusionVector::new(0.0f32, 0.0f32, 0.0f32);
+    let acc = FusionVector::new(0.0f32, 0.0f32, 1.0f32);
+    let mag = FusionVector::new(1.0f32, 0.0f32, 0.0f32);
+    let diverging_gyr = FusionVector::new(f32::MAX, f32::MAX, f32::MAX);
+    assert_eq!(fusion.try_update_micros(gyr, acc, mag, 10_000u64), Ok(FusionDtStatus::Measured));
+    assert_eq!(fusion.try_update_micros(gyr, acc, FusionVector::new(f32::NAN, 0.0f32, 0.0f32), 20_000u64), Err(FusionError::NonFiniteMagnetometer));
+    assert_eq!(fusion.try_update_no_mag_micros(diverging_gyr, acc, 1_000_000_000u64), Err(FusionError::NonFiniteQuaternion));
+    assert!(matches!(fusion.last_timestamp, Some(FusionTimestamp::Microseconds(10_000u64))));
+
+    assert_eq!(fusion.try_update_micros_u32(gyr, acc, mag, 20_000u32), Ok(FusionDtStatus::Measured));
+    assert_eq!(fusion.try_update_no_mag_micros_u32(diverging_gyr, acc, 1_000_000_000u32), Err(FusionError::NonFiniteQuaternion));
+    assert_eq!(fusion.try_update_external_heading_micros_u32(gyr, acc, f32::NAN, 30_000u32), Err(FusionError::NonFiniteHeading));
+    assert_eq!(fusion.try_update_external_heading_micros(gyr, acc, 0.0f32, 30_000u64), Ok(FusionDtStatus::Measured));
+    assert!(matches!(fusion.last_timestamp, Some(FusionTimestamp::Microseconds(30_000u64))));
+
+    assert_eq!(fusion.try_update_secs_f64(gyr, acc, mag, 0.04f64), Ok(FusionDtStatus::Measured));
+    assert_eq!(fusion.try_update_no_mag_secs_f64(gyr, acc, f64::NAN), Err(FusionError::NonFiniteTimestamp));
+    assert_eq!(fusion.try_update_external_heading_secs_f64(gyr, acc, 0.0f32, f64::INFINITY), Err(FusionError::NonFiniteTimestamp));
+    assert_eq!(fusion.try_update_no_mag_secs_f64(gyr, acc, 0.05f64), Ok(FusionDtStatus::Measured));
+    assert!(fusion.quaternion().is_finite());
+    assert!(matches!(fusion.last_timestamp, Some(FusionTimestamp::Seconds(timestamp)) if timestamp == 0.05f64));
+}
+
+#[test]
+fn temp_offset_test() {
+    let mut fusion = Fusion::new(100, FusionAhrsSettings::new());
+    fusion.temp_offset = Some(FusionGyrTempOffset::new(100));
+    fusion.set_temperature(30.0f32);
+    for timestamp in 1..=600 {
+        fusion.update_no_mag(FusionVector::new(1.0f32, 0.0f32, 0.0f32), FusionVector::new(0.0f32, 0.0f32, 1.0f32), timestamp as f32 * 0.01f32);
+    }
+    // Only the temperature-compensated offset is learned
+    assert_eq!(fusion.offset.gyroscope_offset, FusionVector::zero());
+    assert_eq!(fusion.temp_offset.unwrap().offset(30.0f32), FusionVector::new(1.0f32, 0.0f32, 0.0f32));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_test() {
+    let mut fusion = Fusion::new(100, FusionAhrsSettings::new());
+    fusion.soft_iron_matrix = FusionMatrix::from([[1.1f32, 0.0f32, 0.0f32], [0.0f32, 0.9f32, 0.0f32], [0.0f32, 0.0f32, 1.0f32]]);
+    fusion.update_no_mag(FusionVector::new(1.0f32, 2.0f32, 3.0f32), FusionVector::new(0.0f32, 0.0f32, 1.0f32), 0.01f32);
+    let json = serde_json::to_string(&fusion).unwrap();
+    let restored: Fusion = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, fusion);
+}
+
+#[cfg(feature = "libm")]
+#[test]
+fn orientation_filter_test() {
+    // Integrates the gyroscope only, to check what the pipeline passes to the filter
+    #[derive(Clone)]
+    struct GyroscopeIntegrator {
+        quaternion: FusionQuaternion<f64>,
+        gyr: FusionVector<f64>,
+        mag: Option<FusionVector<f64>>,
+    }
+    impl OrientationFilter<f64> for GyroscopeIntegrator {
+        fn update(&mut self, gyr: FusionVector<f64>, _acc: FusionVector<f64>, mag: Option<FusionVector<f64>>, delta_t: f64) {
+            self.gyr = gyr;
+            self.mag = mag;
+            self.quaternion = self.quaternion * FusionQuaternion::exp(gyr * (delta_t * core::f64::consts::PI / 180.0f64));
+        }
+        fn quaternion(&self) -> FusionQuaternion<f64> {
+            self.quaternion
+        }
+        fn set_quaternion(&mut self, quaternion: FusionQuaternion<f64>) {
+            self.quaternion = quaternion;
+        }
+        fn reset(&mut self) {
+            self.quaternion = FusionQuaternion::identity();
+        }
+        fn flags(&self) -> FusionAhrsFlags {
+            FusionAhrsFlags::default()
+        }
+    }
+
+    let filter = GyroscopeIntegrator { quaternion: FusionQuaternion::identity(), gyr: FusionVector::zero(), mag: None };
+    let mut fusion = Fusion::with_filter(100, filter);
+    fusion.gyr_alignment = FusionAxesAlignment::PYNXPZ;
+    fusion.gyr_sensitivity = FusionVector::new(2.0f64, 2.0f64, 2.0f64);
+    fusion.hard_iron_offset = FusionVector::new(0.5f64, 0.0f64, 0.0f64);
+    for timestamp in 1..=100 {
+        fusion.update(FusionVector::new(0.0f64, 45.0f64, 0.0f64), FusionVector::new(0.0f64, 0.0f64, 1.0f64), FusionVector::new(1.0f64, 0.0f64, 0.0f64), timestamp as f64 * 0.01f64);
     }
+    assert!(fusion.internal_ahrs().gyr.approx_eq(&FusionVector::new(90.0f64, 0.0f64, 0.0f64), 1e-9f64));
+    assert!(fusion.internal_ahrs().mag.unwrap().approx_eq(&FusionVector::new(0.5f64, 0.0f64, 0.0f64), 1e-9f64));
+    assert!((fusion.euler().angle.roll - 90.0f64).abs() < 1e-6f64);
+    fusion.update_no_mag(FusionVector::zero(), FusionVector::new(0.0f64, 0.0f64, 1.0f64), 1.01f64);
+    assert!(fusion.internal_ahrs().mag.is_none());
 }
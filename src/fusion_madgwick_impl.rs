@@ -0,0 +1,211 @@
+use crate::{fusion_degrees_to_radians, FusionAhrsFlags, FusionConvention, FusionFloat, FusionQuaternion, FusionVector, MadgwickAhrs, MadgwickAhrsSettings, OrientationFilter};
+use crate::FusionConvention::NWU;
+
+/**
+ * Default gradient descent step size in rad/s.
+ */
+const DEFAULT_BETA: f64 = 0.1f64;
+
+impl MadgwickAhrs {
+    /// Creates the filter with the NWU convention and the default beta of 0.1, use `MadgwickAhrs::default()` for
+    /// other precisions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T: FusionFloat> MadgwickAhrs<T> {
+    /// Creates the filter with a custom convention and gradient descent step size.
+    /// # Examples
+    /// ```
+    /// use imu_fusion::{FusionConvention, FusionVector, MadgwickAhrs, MadgwickAhrsSettings};
+    ///
+    /// let mut settings = MadgwickAhrsSettings::new();
+    /// settings.convention = FusionConvention::NED;
+    /// settings.beta = 0.05f32;
+    /// let mut ahrs = MadgwickAhrs::with_settings(settings);
+    ///
+    /// let gyr = FusionVector::new(0.1f32, -0.2f32, 0.05f32);
+    /// let acc = FusionVector::new(0.0f32, 0.0f32, -1.0f32);
+    /// ahrs.update_no_mag(gyr, acc, 0.01f32);
+    /// ```
+    pub fn with_settings(settings: MadgwickAhrsSettings<T>) -> Self {
+        Self {
+            settings,
+            quaternion: FusionQuaternion::identity(),
+        }
+    }
+
+    /// Changes the parameters, the current orientation is kept.
+    pub fn update_settings(&mut self, settings: MadgwickAhrsSettings<T>) {
+        self.settings = settings;
+    }
+
+    /// 6-axis update with gyroscope data in degrees/s, accelerometer data in g and the delta time in seconds.
+    pub fn update_no_mag(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, dt: T) {
+        let q = self.earth_frame().conjugate() * self.quaternion;
+        let mut step = FusionQuaternion { w: T::ZERO, x: T::ZERO, y: T::ZERO, z: T::ZERO };
+        if !acc.is_zero() {
+            step = Self::gradient(q, FusionVector { x: T::ZERO, y: T::ZERO, z: T::ONE }, acc.normalize());
+        }
+        self.integrate(q, gyr, step, dt);
+    }
+
+    /// 9-axis update, falls back to `update_no_mag` if the magnetometer measurement is zero.
+    pub fn update(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, mag: FusionVector<T>, dt: T) {
+        if mag.is_zero() {
+            self.update_no_mag(gyr, acc, dt);
+            return;
+        }
+        let q = self.earth_frame().conjugate() * self.quaternion;
+        let mut step = FusionQuaternion { w: T::ZERO, x: T::ZERO, y: T::ZERO, z: T::ZERO };
+        if !acc.is_zero() {
+            let mag = mag.normalize();
+            step = Self::gradient(q, FusionVector { x: T::ZERO, y: T::ZERO, z: T::ONE }, acc.normalize()) + Self::gradient(q, Self::magnetic_reference(q, mag), mag);
+        }
+        self.integrate(q, gyr, step, dt);
+    }
+
+    pub fn reset(&mut self) {
+        self.quaternion = FusionQuaternion::identity();
+    }
+
+    /// Integrates the NWU orientation and rotates it back into the earth frame of the convention.
+    fn integrate(&mut self, q: FusionQuaternion<T>, gyr: FusionVector<T>, step: FusionQuaternion<T>, dt: T) {
+        let gyr = FusionVector {
+            x: fusion_degrees_to_radians(gyr.x),
+            y: fusion_degrees_to_radians(gyr.y),
+            z: fusion_degrees_to_radians(gyr.z),
+        };
+        let mut q_dot = q * gyr * T::HALF;
+        let step_norm_squared = step.norm_squared();
+        if step_norm_squared > T::ZERO {
+            q_dot = q_dot - step * (step_norm_squared.inverse_sqrt() * self.settings.beta);
+        }
+        let q = (q + q_dot * dt).normalize();
+        self.quaternion = match self.settings.convention {
+            NWU => q,
+            _ => (self.earth_frame() * q).normalize(),
+        };
+    }
+
+    /// Orientation of the NWU frame in the earth frame of the convention. The filter equations are written for NWU,
+    /// the simplifications they rely on do not carry over to other frames, so other conventions rotate the NWU
+    /// solution instead of rewriting the equations. This keeps the physical orientation independent of the convention.
+    fn earth_frame(&self) -> FusionQuaternion<T> {
+        match self.settings.convention {
+            NWU => FusionQuaternion::identity(),
+            // 90 degrees about up
+            FusionConvention::ENU => FusionQuaternion { w: T::HALF.sqrt(), x: T::ZERO, y: T::ZERO, z: T::HALF.sqrt() },
+            // 180 degrees about north
+            FusionConvention::NED => FusionQuaternion { w: T::ZERO, x: T::ONE, y: T::ZERO, z: T::ZERO },
+        }
+    }
+
+    /// Earth's magnetic field in the NWU frame, with the horizontal component rotated onto north. The field has
+    /// half the magnitude of the measurement, as in the widely deployed reference implementation.
+    fn magnetic_reference(q: FusionQuaternion<T>, mag: FusionVector<T>) -> FusionVector<T> {
+        let h = FusionVector {
+            x: mag.x * (q.w * q.w + q.x * q.x - q.y * q.y - q.z * q.z) + T::TWO * (mag.y * (q.x * q.y - q.w * q.z) + mag.z * (q.x * q.z + q.w * q.y)),
+            y: mag.y * (q.w * q.w - q.x * q.x + q.y * q.y - q.z * q.z) + T::TWO * (mag.x * (q.x * q.y + q.w * q.z) + mag.z * (q.y * q.z - q.w * q.x)),
+            z: mag.z * (q.w * q.w - q.x * q.x - q.y * q.y + q.z * q.z) + T::TWO * (mag.x * (q.x * q.z - q.w * q.y) + mag.y * (q.y * q.z + q.w * q.x)),
+        };
+        FusionVector {
+            x: (h.x * h.x + h.y * h.y).sqrt() * T::HALF,
+            y: T::ZERO,
+            z: h.z * T::HALF,
+        }
+    }
+
+    /// Gradient of the objective function, the earth frame reference rotated into the sensor frame minus the
+    /// measurement, as the Jacobian transposed times the objective function.
+    fn gradient(q: FusionQuaternion<T>, reference: FusionVector<T>, measurement: FusionVector<T>) -> FusionQuaternion<T> {
+        let d = reference * T::TWO;
+        let f = FusionVector {
+            x: d.x * (T::HALF - q.y * q.y - q.z * q.z) + d.y * (q.w * q.z + q.x * q.y) + d.z * (q.x * q.z - q.w * q.y),
+            y: d.x * (q.x * q.y - q.w * q.z) + d.y * (T::HALF - q.x * q.x - q.z * q.z) + d.z * (q.w * q.x + q.y * q.z),
+            z: d.x * (q.w * q.y + q.x * q.z) + d.y * (q.y * q.z - q.w * q.x) + d.z * (T::HALF - q.x * q.x - q.y * q.y),
+        } - measurement;
+        FusionQuaternion {
+            w: (d.y * q.z - d.z * q.y) * f.x + (d.z * q.x - d.x * q.z) * f.y + (d.x * q.y - d.y * q.x) * f.z,
+            x: (d.y * q.y + d.z * q.z) * f.x + (d.x * q.y - T::TWO * d.y * q.x + d.z * q.w) * f.y + (d.x * q.z - d.y * q.w - T::TWO * d.z * q.x) * f.z,
+            y: (d.y * q.x - T::TWO * d.x * q.y - d.z * q.w) * f.x + (d.x * q.x + d.z * q.z) * f.y + (d.x * q.w + d.y * q.z - T::TWO * d.z * q.y) * f.z,
+            z: (d.y * q.w - T::TWO * d.x * q.z + d.z * q.x) * f.x + (d.z * q.y - d.x * q.w - T::TWO * d.y * q.z) * f.y + (d.x * q.x + d.y * q.y) * f.z,
+        }
+    }
+}
+
+impl<T: FusionFloat> OrientationFilter<T> for MadgwickAhrs<T> {
+    fn update(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, mag: Option<FusionVector<T>>, delta_t: T) {
+        match mag {
+            Some(mag) => MadgwickAhrs::update(self, gyr, acc, mag, delta_t),
+            None => self.update_no_mag(gyr, acc, delta_t),
+        }
+    }
+
+    fn quaternion(&self) -> FusionQuaternion<T> {
+        self.quaternion
+    }
+
+    fn set_quaternion(&mut self, quaternion: FusionQuaternion<T>) {
+        self.quaternion = quaternion.normalize();
+    }
+
+    fn reset(&mut self) {
+        MadgwickAhrs::reset(self);
+    }
+
+    fn flags(&self) -> FusionAhrsFlags {
+        FusionAhrsFlags::default()
+    }
+}
+
+impl<T: FusionFloat> Default for MadgwickAhrs<T> {
+    fn default() -> Self {
+        Self::with_settings(MadgwickAhrsSettings::default())
+    }
+}
+
+impl MadgwickAhrsSettings {
+    /// Creates settings with default values, use `MadgwickAhrsSettings::default()` for other precisions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T: FusionFloat> Default for MadgwickAhrsSettings<T> {
+    fn default() -> Self {
+        Self {
+            convention: NWU,
+            beta: T::from_f64(DEFAULT_BETA),
+        }
+    }
+}
+
+#[cfg(feature = "libm")]
+#[test]
+fn convention_test() {
+    // Level sensor with the x axis pointing north, magnetic field inclined 45 degrees downwards
+    let cases = [
+        (FusionConvention::NWU, FusionVector::new(0.0f32, 0.0f32, 1.0f32), FusionVector::new(1.0f32, 0.0f32, -1.0f32), FusionQuaternion::identity()),
+        (FusionConvention::ENU, FusionVector::new(0.0f32, 0.0f32, 1.0f32), FusionVector::new(1.0f32, 0.0f32, -1.0f32), FusionQuaternion::from_axis_angle(FusionVector::new(0.0f32, 0.0f32, 1.0f32), fusion_degrees_to_radians(90.0f32))),
+        (FusionConvention::NED, FusionVector::new(0.0f32, 0.0f32, -1.0f32), FusionVector::new(1.0f32, 0.0f32, 1.0f32), FusionQuaternion::identity()),
+    ];
+    for (convention, acc, mag, expected) in cases {
+        let mut ahrs = MadgwickAhrs::with_settings(MadgwickAhrsSettings { convention, beta: 0.5f32 });
+        // Start 30 degrees off in roll and heading
+        ahrs.quaternion = FusionQuaternion::from_axis_angle(FusionVector::new(1.0f32, 0.0f32, 1.0f32), fusion_degrees_to_radians(30.0f32));
+        for _ in 0..2000 {
+            ahrs.update(FusionVector::zero(), acc, mag, 0.01f32);
+        }
+        assert!(ahrs.quaternion.angle_between(&expected) < fusion_degrees_to_radians(0.5f32), "{:?} {}", convention, ahrs.quaternion);
+    }
+}
+
+#[test]
+fn set_quaternion_test() {
+    let mut ahrs = MadgwickAhrs::new();
+    OrientationFilter::set_quaternion(&mut ahrs, FusionQuaternion { w: 0.0f32, x: 0.0f32, y: 0.0f32, z: 3.0f32 });
+    // Normalised with the fast inverse square root
+    assert!((ahrs.quaternion.z - 1.0f32).abs() < 0.001f32, "{}", ahrs.quaternion);
+}
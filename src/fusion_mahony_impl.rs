@@ -0,0 +1,197 @@
+use crate::{fusion_degrees_to_radians, fusion_radians_to_degrees, FusionAhrsFlags, FusionFloat, FusionQuaternion, FusionVector, MahonyAhrs, MahonyAhrsSettings, OrientationFilter};
+use crate::fusion_ahrs_impl::{feedback, half_gravity, half_magnetic};
+use crate::FusionConvention::NWU;
+
+impl MahonyAhrs {
+    /// Creates the filter with default settings, use `MahonyAhrs::default()` for other precisions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T: FusionFloat> MahonyAhrs<T> {
+    /// Creates the filter with custom gains and anti-windup limits.
+    /// # Examples
+    /// ```
+    /// use imu_fusion::{MahonyAhrs, MahonyAhrsSettings, FusionVector};
+    ///
+    /// let mut settings = MahonyAhrsSettings::new();
+    /// settings.ki = 0.05f32;
+    /// settings.bias_limit = 2.0f32;
+    /// let mut ahrs = MahonyAhrs::with_settings(settings);
+    ///
+    /// let gyr = FusionVector::new(0.1f32, -0.2f32, 0.05f32);
+    /// let acc = FusionVector::new(0.0f32, 0.0f32, 1.0f32);
+    /// ahrs.update_no_mag(gyr, acc, 0.01f32);
+    /// let corrected_gyr = gyr - ahrs.gyr_bias;
+    /// ```
+    pub fn with_settings(settings: MahonyAhrsSettings<T>) -> Self {
+        Self {
+            settings,
+            quaternion: FusionQuaternion::identity(),
+            gyr_bias: FusionVector::zero(),
+        }
+    }
+
+    /// Changes the parameters, the estimated bias is clamped to the new limit.
+    pub fn update_settings(&mut self, settings: MahonyAhrsSettings<T>) {
+        self.settings = settings;
+        self.gyr_bias = self.clamp_bias(self.gyr_bias);
+    }
+
+    pub fn update_no_mag(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, dt: T) {
+        self.update(gyr, acc, FusionVector::zero(), dt);
+    }
+
+    /// Updates the orientation with gyroscope data in degrees/s, accelerometer data in g, magnetometer data in
+    /// arbitrary units and the delta time in seconds. The magnetometer is ignored if zero.
+    pub fn update(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, mag: FusionVector<T>, dt: T) {
+        // Calculate error between measured and estimated directions, no feedback without accelerometer
+        let mut half_error = FusionVector::zero();
+        if !acc.is_zero() {
+            let half_gravity = half_gravity(self.quaternion, self.settings.convention);
+            half_error = feedback(acc.normalize(), half_gravity);
+            if !mag.is_zero() {
+                let half_magnetic = half_magnetic(self.quaternion, self.settings.convention);
+                half_error += feedback(half_gravity.cross_product(&mag).normalize(), half_magnetic);
+            }
+        }
+        // Integrate error into the bias estimate unless rotating faster than the limit, the bias itself is not rotation
+        let corrected_gyr = gyr - self.gyr_bias;
+        let rate_limit = self.settings.bias_rate_limit;
+        if rate_limit == T::ZERO || (corrected_gyr.x.abs() <= rate_limit && corrected_gyr.y.abs() <= rate_limit && corrected_gyr.z.abs() <= rate_limit) {
+            let bias = self.gyr_bias - half_error * fusion_radians_to_degrees(T::TWO * self.settings.ki * dt);
+            self.gyr_bias = self.clamp_bias(bias);
+        }
+        // Convert bias corrected gyroscope to radians per second scaled by 0.5 and apply proportional feedback
+        let adjusted_half_gyroscope = (gyr - self.gyr_bias) * fusion_degrees_to_radians(T::HALF) + half_error * self.settings.kp;
+        // Integrate rate of change of quaternion
+        self.quaternion = self.quaternion + self.quaternion * (adjusted_half_gyroscope * dt);
+        // Normalise quaternion
+        self.quaternion = self.quaternion.normalize();
+    }
+
+    /// Resets the orientation and the estimated bias.
+    pub fn reset(&mut self) {
+        self.quaternion = FusionQuaternion::identity();
+        self.gyr_bias = FusionVector::zero();
+    }
+
+    fn clamp_bias(&self, bias: FusionVector<T>) -> FusionVector<T> {
+        let limit = self.settings.bias_limit;
+        if limit == T::ZERO {
+            return bias;
+        }
+        bias.clamp(&FusionVector::new(-limit, -limit, -limit), &FusionVector::new(limit, limit, limit))
+    }
+}
+
+impl<T: FusionFloat> OrientationFilter<T> for MahonyAhrs<T> {
+    fn update(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, mag: Option<FusionVector<T>>, delta_t: T) {
+        MahonyAhrs::update(self, gyr, acc, mag.unwrap_or_default(), delta_t);
+    }
+
+    fn quaternion(&self) -> FusionQuaternion<T> {
+        self.quaternion
+    }
+
+    fn set_quaternion(&mut self, quaternion: FusionQuaternion<T>) {
+        self.quaternion = quaternion.normalize();
+    }
+
+    fn reset(&mut self) {
+        MahonyAhrs::reset(self);
+    }
+
+    fn flags(&self) -> FusionAhrsFlags {
+        FusionAhrsFlags::default()
+    }
+}
+
+impl<T: FusionFloat> Default for MahonyAhrs<T> {
+    fn default() -> Self {
+        Self::with_settings(MahonyAhrsSettings::default())
+    }
+}
+
+impl MahonyAhrsSettings {
+    /// Creates settings with default values, use `MahonyAhrsSettings::default()` for other precisions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T: FusionFloat> Default for MahonyAhrsSettings<T> {
+    fn default() -> Self {
+        Self {
+            convention: NWU,
+            kp: T::ONE,
+            ki: T::from_f64(0.1f64),
+            bias_limit: T::from_f64(10.0f64),
+            bias_rate_limit: T::ZERO,
+        }
+    }
+}
+
+#[test]
+fn bias_test() {
+    // Stationary level sensor facing north with a constant gyroscope bias
+    let bias = FusionVector::new(0.5f32, -0.3f32, 0.2f32);
+    let acc = FusionVector::new(0.0f32, 0.0f32, 1.0f32);
+    let mag = FusionVector::new(1.0f32, 0.0f32, -1.0f32);
+    let mut ahrs = MahonyAhrs::new();
+    for _ in 0..10000 {
+        ahrs.update(bias, acc, mag, 0.01f32);
+    }
+    assert!(ahrs.gyr_bias.approx_eq(&bias, 0.01f32), "{}", ahrs.gyr_bias);
+    assert!(ahrs.quaternion.angle_between(&FusionQuaternion::identity()) < fusion_degrees_to_radians(0.1f32));
+
+    // Bias is held at the anti-windup limit
+    let mut settings = MahonyAhrsSettings::new();
+    settings.bias_limit = 0.25f32;
+    ahrs.update_settings(settings.clone());
+    assert!(ahrs.gyr_bias.approx_eq(&FusionVector::new(0.25f32, -0.25f32, 0.2f32), 0.01f32));
+    for _ in 0..1000 {
+        ahrs.update(bias, acc, mag, 0.01f32);
+    }
+    assert!(ahrs.gyr_bias.x == 0.25f32 && ahrs.gyr_bias.y == -0.25f32, "{}", ahrs.gyr_bias);
+
+    // Bias keeps tracking a drift while the bias corrected rate is below the rate limit
+    let mut settings = MahonyAhrsSettings::new();
+    settings.bias_rate_limit = 0.1f32;
+    ahrs.update_settings(settings);
+    ahrs.gyr_bias = bias;
+    let drifted_bias = bias + FusionVector::new(0.05f32, 0.05f32, -0.05f32);
+    for _ in 0..10000 {
+        ahrs.update(drifted_bias, acc, mag, 0.01f32);
+    }
+    assert!(ahrs.gyr_bias.approx_eq(&drifted_bias, 0.01f32), "{}", ahrs.gyr_bias);
+
+    // Bias is not integrated while rotating faster than the rate limit
+    let gyr_bias = ahrs.gyr_bias;
+    for _ in 0..100 {
+        ahrs.update(drifted_bias + FusionVector::new(0.0f32, 0.0f32, 90.0f32), acc, mag, 0.01f32);
+    }
+    assert_eq!(ahrs.gyr_bias, gyr_bias);
+}
+
+#[test]
+fn convention_test() {
+    use crate::FusionConvention;
+    // Level sensor with the x axis pointing north, magnetic field inclined 45 degrees downwards
+    let cases = [
+        (FusionConvention::NWU, FusionVector::new(0.0f32, 0.0f32, 1.0f32), FusionVector::new(1.0f32, 0.0f32, -1.0f32), FusionQuaternion::identity()),
+        (FusionConvention::ENU, FusionVector::new(0.0f32, 0.0f32, 1.0f32), FusionVector::new(1.0f32, 0.0f32, -1.0f32), FusionQuaternion::from_axis_angle(FusionVector::new(0.0f32, 0.0f32, 1.0f32), fusion_degrees_to_radians(90.0f32))),
+        (FusionConvention::NED, FusionVector::new(0.0f32, 0.0f32, -1.0f32), FusionVector::new(1.0f32, 0.0f32, 1.0f32), FusionQuaternion::identity()),
+    ];
+    for (convention, acc, mag, expected) in cases {
+        let mut ahrs = MahonyAhrs::with_settings(MahonyAhrsSettings { convention, ..Default::default() });
+        // Start 30 degrees off in roll and heading
+        OrientationFilter::set_quaternion(&mut ahrs, FusionQuaternion::from_axis_angle(FusionVector::new(1.0f32, 0.0f32, 1.0f32), fusion_degrees_to_radians(30.0f32)));
+        for _ in 0..10000 {
+            ahrs.update(FusionVector::zero(), acc, mag, 0.01f32);
+        }
+        assert!(ahrs.quaternion.angle_between(&expected) < fusion_degrees_to_radians(0.5f32), "{:?} {}", convention, ahrs.quaternion);
+        assert!(ahrs.gyr_bias.approx_eq(&FusionVector::zero(), 0.01f32), "{:?} {}", convention, ahrs.gyr_bias);
+    }
+}
@@ -1,8 +1,9 @@
-use core::ops;
-use crate::{FusionMatrix, FusionQuaternion, FusionVector};
+use core::{fmt, ops};
+use crate::{FusionFloat, FusionMatrix, FusionQuaternion, FusionVector};
 
-impl FusionMatrix {
-    pub fn new(xx: f32, xy: f32, xz: f32, yx: f32, yy: f32, yz: f32, zx: f32, zy: f32, zz: f32) -> Self {
+impl<T: FusionFloat> FusionMatrix<T> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(xx: T, xy: T, xz: T, yx: T, yy: T, yz: T, zx: T, zy: T, zz: T) -> Self {
         Self {
             xx,
             xy,
@@ -18,25 +19,99 @@ impl FusionMatrix {
 
 
     pub fn identity() -> Self {
-        const VALUE: FusionMatrix =
-            FusionMatrix {
-                xx: 1.0f32,
-                xy: 0.0f32,
-                xz: 0.0f32,
-                yx: 0.0f32,
-                yy: 1.0f32,
-                yz: 0.0f32,
-                zx: 0.0f32,
-                zy: 0.0f32,
-                zz: 1.0f32,
-            };
-        VALUE
+        Self {
+            xx: T::ONE,
+            xy: T::ZERO,
+            xz: T::ZERO,
+            yx: T::ZERO,
+            yy: T::ONE,
+            yz: T::ZERO,
+            zx: T::ZERO,
+            zy: T::ZERO,
+            zz: T::ONE,
+        }
+    }
+
+    /// Returns true if each element differs by at most epsilon.
+    pub fn approx_eq(&self, rhs: &Self, epsilon: T) -> bool {
+        let rows: [[T; 3]; 3] = (*self - *rhs).into();
+        rows.iter().flatten().all(|value| value.abs() <= epsilon)
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self::new(self.xx, self.yx, self.zx, self.xy, self.yy, self.zy, self.xz, self.yz, self.zz)
+    }
+
+    pub fn determinant(&self) -> T {
+        self.xx * (self.yy * self.zz - self.yz * self.zy)
+            - self.xy * (self.yx * self.zz - self.yz * self.zx)
+            + self.xz * (self.yx * self.zy - self.yy * self.zx)
+    }
+
+    /// Returns the inverse, or None if the matrix is singular.
+    /// # Examples
+    /// ```
+    /// use imu_fusion::FusionMatrix;
+    ///
+    /// let soft_iron_matrix = FusionMatrix::from([[1.1f32, 0.0f32, 0.0f32], [0.0f32, 0.9f32, 0.0f32], [0.0f32, 0.0f32, 1.0f32]]);
+    /// let inverse = soft_iron_matrix.inverse().unwrap();
+    /// assert!((inverse.yy - 1.0f32 / 0.9f32).abs() < 1e-6);
+    /// ```
+    pub fn inverse(&self) -> Option<Self> {
+        let determinant = self.determinant();
+        if determinant == T::ZERO {
+            return None;
+        }
+        let adjugate = Self {
+            xx: self.yy * self.zz - self.yz * self.zy,
+            xy: self.xz * self.zy - self.xy * self.zz,
+            xz: self.xy * self.yz - self.xz * self.yy,
+            yx: self.yz * self.zx - self.yx * self.zz,
+            yy: self.xx * self.zz - self.xz * self.zx,
+            yz: self.xz * self.yx - self.xx * self.yz,
+            zx: self.yx * self.zy - self.yy * self.zx,
+            zy: self.xy * self.zx - self.xx * self.zy,
+            zz: self.xx * self.yy - self.xy * self.yx,
+        };
+        Some(adjugate * (T::ONE / determinant))
     }
 }
 
-impl ops::Mul<FusionVector> for FusionMatrix {
-    type Output = FusionVector;
-    fn mul(self, rhs: FusionVector) -> Self::Output {
+impl<T: FusionFloat> Default for FusionMatrix<T> {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl<T: FusionFloat> fmt::Display for FusionMatrix<T> {
+    /// Formats as [[xx, xy, xz], [yx, yy, yz], [zx, zy, zz]], applying the precision to each element.
+    /// # Examples
+    /// ```
+    /// use imu_fusion::FusionMatrix;
+    ///
+    /// let m: FusionMatrix = FusionMatrix::identity();
+    /// assert_eq!(format!("{:.1}", m), "[[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows: [[T; 3]; 3] = (*self).into();
+        f.write_str("[")?;
+        for (i, row) in rows.iter().enumerate() {
+            f.write_str(if i == 0 { "[" } else { ", [" })?;
+            for (j, value) in row.iter().enumerate() {
+                if j > 0 {
+                    f.write_str(", ")?;
+                }
+                fmt::Display::fmt(value, f)?;
+            }
+            f.write_str("]")?;
+        }
+        f.write_str("]")
+    }
+}
+
+impl<T: FusionFloat> ops::Mul<FusionVector<T>> for FusionMatrix<T> {
+    type Output = FusionVector<T>;
+    fn mul(self, rhs: FusionVector<T>) -> Self::Output {
         FusionVector {
             x: self.xx * rhs.x + self.xy * rhs.y + self.xz * rhs.z,
             y: self.yx * rhs.x + self.yy * rhs.y + self.yz * rhs.z,
@@ -45,28 +120,102 @@ impl ops::Mul<FusionVector> for FusionMatrix {
     }
 }
 
-impl From<FusionQuaternion> for FusionMatrix {
-    fn from(q: FusionQuaternion) -> Self {
-        let qwqw = q.w * q.w;
-        let qwqx = q.w * q.x;
-        let qwqy = q.w * q.y;
-        let qwqz = q.w * q.z;
-        let qxqx = q.x * q.x;
-        let qxqy = q.x * q.y;
-        let qxqz = q.x * q.z;
-        let qyqy = q.y * q.y;
-        let qyqz = q.y * q.z;
-        let qzqz = q.z * q.z;
+impl<T: FusionFloat> ops::Mul for FusionMatrix<T> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self {
+            xx: self.xx * rhs.xx + self.xy * rhs.yx + self.xz * rhs.zx,
+            xy: self.xx * rhs.xy + self.xy * rhs.yy + self.xz * rhs.zy,
+            xz: self.xx * rhs.xz + self.xy * rhs.yz + self.xz * rhs.zz,
+            yx: self.yx * rhs.xx + self.yy * rhs.yx + self.yz * rhs.zx,
+            yy: self.yx * rhs.xy + self.yy * rhs.yy + self.yz * rhs.zy,
+            yz: self.yx * rhs.xz + self.yy * rhs.yz + self.yz * rhs.zz,
+            zx: self.zx * rhs.xx + self.zy * rhs.yx + self.zz * rhs.zx,
+            zy: self.zx * rhs.xy + self.zy * rhs.yy + self.zz * rhs.zy,
+            zz: self.zx * rhs.xz + self.zy * rhs.yz + self.zz * rhs.zz,
+        }
+    }
+}
+
+impl<T: FusionFloat> ops::Mul<T> for FusionMatrix<T> {
+    type Output = Self;
+    fn mul(self, rhs: T) -> Self::Output {
+        Self {
+            xx: self.xx * rhs,
+            xy: self.xy * rhs,
+            xz: self.xz * rhs,
+            yx: self.yx * rhs,
+            yy: self.yy * rhs,
+            yz: self.yz * rhs,
+            zx: self.zx * rhs,
+            zy: self.zy * rhs,
+            zz: self.zz * rhs,
+        }
+    }
+}
+
+impl<T: FusionFloat> ops::Add for FusionMatrix<T> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
         Self {
-            xx: 2.0f32 * (qwqw - 0.5f32 + qxqx),
-            xy: 2.0f32 * (qxqy - qwqz),
-            xz: 2.0f32 * (qxqz + qwqy),
-            yx: 2.0f32 * (qxqy + qwqz),
-            yy: 2.0f32 * (qwqw - 0.5f32 + qyqy),
-            yz: 2.0f32 * (qyqz - qwqx),
-            zx: 2.0f32 * (qxqz - qwqy),
-            zy: 2.0f32 * (qyqz + qwqx),
-            zz: 2.0f32 * (qwqw - 0.5f32 + qzqz),
+            xx: self.xx + rhs.xx,
+            xy: self.xy + rhs.xy,
+            xz: self.xz + rhs.xz,
+            yx: self.yx + rhs.yx,
+            yy: self.yy + rhs.yy,
+            yz: self.yz + rhs.yz,
+            zx: self.zx + rhs.zx,
+            zy: self.zy + rhs.zy,
+            zz: self.zz + rhs.zz,
         }
     }
 }
+
+impl<T: FusionFloat> ops::Sub for FusionMatrix<T> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + rhs * -T::ONE
+    }
+}
+
+impl<T: FusionFloat> From<FusionQuaternion<T>> for FusionMatrix<T> {
+    fn from(q: FusionQuaternion<T>) -> Self {
+        q.rotation()
+    }
+}
+
+impl<T: FusionFloat> From<[[T; 3]; 3]> for FusionMatrix<T> {
+    /// Creates a matrix from rows.
+    fn from(rows: [[T; 3]; 3]) -> Self {
+        Self::new(rows[0][0], rows[0][1], rows[0][2], rows[1][0], rows[1][1], rows[1][2], rows[2][0], rows[2][1], rows[2][2])
+    }
+}
+
+impl<T: FusionFloat> From<FusionMatrix<T>> for [[T; 3]; 3] {
+    fn from(m: FusionMatrix<T>) -> Self {
+        [[m.xx, m.xy, m.xz], [m.yx, m.yy, m.yz], [m.zx, m.zy, m.zz]]
+    }
+}
+
+#[cfg(feature = "libm")]
+#[test]
+fn algebra_test() {
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+    let a = FusionMatrix::from([[2.0f64, 0.0f64, 1.0f64], [1.0f64, 3.0f64, 0.0f64], [0.0f64, 1.0f64, 4.0f64]]);
+    assert!(close(a.determinant(), 25.0f64));
+    let rows: [[f64; 3]; 3] = (a * a.inverse().unwrap() - FusionMatrix::identity()).into();
+    assert!(rows.iter().flatten().all(|&value| close(value, 0.0f64)));
+    assert!(FusionMatrix::<f64>::from([[1.0f64, 2.0f64, 3.0f64]; 3]).inverse().is_none());
+    let t = a.transpose();
+    assert!(close(t.xz, 0.0f64) && close(t.zx, 1.0f64) && close((a + a).yx, 2.0f64));
+    let v = (a * t) * FusionVector::new(1.0f64, 1.0f64, 1.0f64);
+    let w = a * (t * FusionVector::new(1.0f64, 1.0f64, 1.0f64));
+    assert!(close(v.x, w.x) && close(v.y, w.y) && close(v.z, w.z));
+
+    // Matrix to quaternion round trip, including rotations near 180 degrees about each axis
+    for (axis, angle) in [((1.0f64, 2.0f64, 3.0f64), 0.7f64), ((1.0f64, 0.01f64, 0.0f64), 3.1f64), ((0.0f64, 1.0f64, 0.01f64), 3.1f64), ((0.01f64, 0.0f64, 1.0f64), -3.1f64)] {
+        let q = FusionQuaternion::from_axis_angle(FusionVector::new(axis.0, axis.1, axis.2), angle);
+        let result = FusionQuaternion::from(q.rotation());
+        assert!(close(q.angle_between(&result), 0.0f64) && close(result.norm(), 1.0f64));
+    }
+}
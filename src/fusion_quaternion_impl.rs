@@ -1,38 +1,157 @@
-use core::ops;
-#[allow(unused_imports)]
-use libm::{asinf, atan2f, sqrtf};
-use crate::{Angle, asin_safe, fusion_fast_inverse_sqrt, fusion_radians_to_degrees, FusionEuler, FusionMatrix, FusionQuaternion, FusionVector};
+use core::{fmt, ops};
+use crate::{Angle, asin_safe, fusion_degrees_to_radians, fusion_radians_to_degrees, FusionEuler, FusionEulerSequence, FusionFloat, FusionMatrix, FusionQuaternion, FusionVector};
 
-impl FusionQuaternion {
+impl<T: FusionFloat> FusionQuaternion<T> {
     pub fn identity() -> Self {
-        const VALUE: FusionQuaternion =
-            FusionQuaternion {
-                w: 1.0f32,
-                x: 0.0f32,
-                y: 0.0f32,
-                z: 0.0f32,
-            };
-        VALUE
+        Self {
+            w: T::ONE,
+            x: T::ZERO,
+            y: T::ZERO,
+            z: T::ZERO,
+        }
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.w.is_finite() && self.x.is_finite() && self.y.is_finite() && self.z.is_finite()
     }
 
     pub fn normalize(&self) -> Self {
-        #[cfg(feature = "fusion-use-normal-sqrt")]
-        {
-            *self * 1.0f32 / sqrtf(self.w * self.w + self.x * self.x + self.y * self.y + self.z * self.z)
+        *self * (self.w * self.w + self.x * self.x + self.y * self.y + self.z * self.z).inverse_sqrt()
+    }
+
+    /// Returns true if each component differs by at most epsilon. q and -q are the same rotation but are not
+    /// approximately equal, use `angle_between` to compare rotations.
+    pub fn approx_eq(&self, rhs: &Self, epsilon: T) -> bool {
+        (self.w - rhs.w).abs() <= epsilon && (self.x - rhs.x).abs() <= epsilon && (self.y - rhs.y).abs() <= epsilon && (self.z - rhs.z).abs() <= epsilon
+    }
+
+    pub fn conjugate(&self) -> Self {
+        Self {
+            w: self.w,
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+
+    pub fn dot(&self, rhs: &Self) -> T {
+        self.w * rhs.w + self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+    }
+
+    pub fn norm_squared(&self) -> T {
+        self.dot(self)
+    }
+
+    pub fn norm(&self) -> T {
+        self.norm_squared().sqrt()
+    }
+
+    /// Returns the multiplicative inverse, which is the conjugate for a unit quaternion.
+    pub fn inverse(&self) -> Self {
+        self.conjugate() * (T::ONE / self.norm_squared())
+    }
+
+    /// Rotates a vector by a unit quaternion, e.g. from the sensor frame to the earth frame for the AHRS quaternion.
+    /// Same result as `self.rotation() * vector`.
+    pub fn rotate_vector(&self, vector: FusionVector<T>) -> FusionVector<T> {
+        let q = FusionVector::new(self.x, self.y, self.z);
+        let t = q.cross_product(&vector) * T::TWO;
+        vector + t * self.w + q.cross_product(&t)
+    }
+
+    /// Returns the angle in radians of the smallest rotation between two unit quaternions.
+    pub fn angle_between(&self, rhs: &Self) -> T {
+        let difference = self.conjugate() * *rhs;
+        let vector_norm = (difference.x * difference.x + difference.y * difference.y + difference.z * difference.z).sqrt();
+        T::TWO * vector_norm.atan2(difference.w.abs())
+    }
+
+    /// Creates a unit quaternion rotating by angle in radians about axis, the axis does not need to be normalised.
+    pub fn from_axis_angle(axis: FusionVector<T>, angle: T) -> Self {
+        let axis_norm = axis.dot_product(&axis).sqrt();
+        if axis_norm == T::ZERO {
+            return Self::identity();
         }
-        #[cfg(not(feature = "fusion-use-normal-sqrt"))]
-        {
-            *self * fusion_fast_inverse_sqrt(self.w * self.w + self.x * self.x + self.y * self.y + self.z * self.z)
+        let half_angle = angle * T::HALF;
+        let scale = half_angle.sin() / axis_norm;
+        Self {
+            w: half_angle.cos(),
+            x: axis.x * scale,
+            y: axis.y * scale,
+            z: axis.z * scale,
+        }
+    }
+
+    /// Returns the unit axis and the angle in radians, from 0 to 2 pi, of a unit quaternion.
+    /// The axis is +X for the identity rotation.
+    pub fn to_axis_angle(&self) -> (FusionVector<T>, T) {
+        let vector = FusionVector::new(self.x, self.y, self.z);
+        let vector_norm = vector.dot_product(&vector).sqrt();
+        if vector_norm == T::ZERO {
+            return (FusionVector::new(T::ONE, T::ZERO, T::ZERO), T::ZERO);
+        }
+        (vector * (T::ONE / vector_norm), T::TWO * vector_norm.atan2(self.w))
+    }
+
+    /// Exponential map, creates a unit quaternion from a rotation vector (axis scaled by angle in radians).
+    /// # Examples
+    /// ```
+    /// use imu_fusion::{FusionQuaternion, FusionVector};
+    ///
+    /// let rotation_vector = FusionVector::new(0.0f32, 0.0f32, core::f32::consts::FRAC_PI_2);
+    /// let q = FusionQuaternion::exp(rotation_vector);
+    /// let v = q.rotate_vector(FusionVector::new(1.0f32, 0.0f32, 0.0f32));
+    /// # #[cfg(feature = "libm")] // micromath is less accurate
+    /// assert!((v.y - 1.0f32).abs() < 1e-6);
+    /// ```
+    pub fn exp(rotation_vector: FusionVector<T>) -> Self {
+        let angle = rotation_vector.dot_product(&rotation_vector).sqrt();
+        let half_angle = angle * T::HALF;
+        // sin(angle / 2) / angle, Taylor series for small angles
+        let scale = if angle < T::from_f64(1e-4) {
+            T::HALF - angle * angle / T::from_f64(48.0f64)
+        } else {
+            half_angle.sin() / angle
+        };
+        Self {
+            w: half_angle.cos(),
+            x: rotation_vector.x * scale,
+            y: rotation_vector.y * scale,
+            z: rotation_vector.z * scale,
         }
     }
 
-    pub fn euler(self) -> FusionEuler {
+    /// Logarithmic map, returns the rotation vector of a unit quaternion with an angle from 0 to pi.
+    pub fn log(&self) -> FusionVector<T> {
+        // q and -q are the same rotation, use the one with the smaller angle
+        let q = if self.w < T::ZERO { -*self } else { *self };
+        let vector = FusionVector::new(q.x, q.y, q.z);
+        let vector_norm = vector.dot_product(&vector).sqrt();
+        if vector_norm < T::from_f64(1e-4) {
+            return vector * (T::TWO / q.w);
+        }
+        vector * (T::TWO * vector_norm.atan2(q.w) / vector_norm)
+    }
+
+    pub fn euler(self) -> FusionEuler<T> {
+        let radians = self.euler_radians();
+        FusionEuler {
+            angle: Angle {
+                roll: fusion_radians_to_degrees(radians.angle.roll),
+                pitch: fusion_radians_to_degrees(radians.angle.pitch),
+                yaw: fusion_radians_to_degrees(radians.angle.yaw),
+            }
+        }
+    }
+
+    /// Same as `euler`, with angles in radians.
+    pub fn euler_radians(self) -> FusionEuler<T> {
         // calculate common terms to avoid repeated operations
         let q = self;
-        let half_minus_qy_squared = 0.5f32 - q.y * q.y;
-        let roll = fusion_radians_to_degrees(atan2f(q.w * q.x + q.y * q.z, half_minus_qy_squared - q.x * q.x));
-        let pitch = fusion_radians_to_degrees(asin_safe(2.0f32 * (q.w * q.y - q.z * q.x)));
-        let yaw = fusion_radians_to_degrees(atan2f(q.w * q.z + q.x * q.y, half_minus_qy_squared - q.z * q.z));
+        let half_minus_qy_squared = T::HALF - q.y * q.y;
+        let roll = (q.w * q.x + q.y * q.z).atan2(half_minus_qy_squared - q.x * q.x);
+        let pitch = asin_safe(T::TWO * (q.w * q.y - q.z * q.x));
+        let yaw = (q.w * q.z + q.x * q.y).atan2(half_minus_qy_squared - q.z * q.z);
 
         FusionEuler {
             angle: Angle {
@@ -43,7 +162,109 @@ impl FusionQuaternion {
         }
     }
 
-    pub fn rotation(self) -> FusionMatrix {
+    /// Creates a quaternion from ZYX roll, pitch and yaw in degrees, the inverse of `euler`.
+    /// # Examples
+    /// ```
+    /// use imu_fusion::{Angle, FusionEuler, FusionQuaternion};
+    ///
+    /// let euler = FusionEuler { angle: Angle { roll: 10.0f32, pitch: -20.0f32, yaw: 30.0f32 } };
+    /// let q = FusionQuaternion::from_euler(euler);
+    /// # #[cfg(feature = "libm")] // micromath is less accurate
+    /// assert!((q.euler().angle.yaw - 30.0f32).abs() < 1e-4);
+    /// ```
+    pub fn from_euler(euler: FusionEuler<T>) -> Self {
+        Self::from_euler_radians(FusionEuler {
+            angle: Angle {
+                roll: fusion_degrees_to_radians(euler.angle.roll),
+                pitch: fusion_degrees_to_radians(euler.angle.pitch),
+                yaw: fusion_degrees_to_radians(euler.angle.yaw),
+            }
+        })
+    }
+
+    /// Same as `from_euler`, with angles in radians.
+    pub fn from_euler_radians(euler: FusionEuler<T>) -> Self {
+        let half_roll = T::HALF * euler.angle.roll;
+        let half_pitch = T::HALF * euler.angle.pitch;
+        let half_yaw = T::HALF * euler.angle.yaw;
+        let (sin_roll, cos_roll) = (half_roll.sin(), half_roll.cos());
+        let (sin_pitch, cos_pitch) = (half_pitch.sin(), half_pitch.cos());
+        let (sin_yaw, cos_yaw) = (half_yaw.sin(), half_yaw.cos());
+        Self {
+            w: cos_roll * cos_pitch * cos_yaw + sin_roll * sin_pitch * sin_yaw,
+            x: sin_roll * cos_pitch * cos_yaw - cos_roll * sin_pitch * sin_yaw,
+            y: cos_roll * sin_pitch * cos_yaw + sin_roll * cos_pitch * sin_yaw,
+            z: cos_roll * cos_pitch * sin_yaw - sin_roll * sin_pitch * cos_yaw,
+        }
+    }
+
+    /// Creates a quaternion from angles in radians of successive intrinsic rotations about the axes of sequence.
+    /// `FusionEulerSequence::ZYX` with `[yaw, pitch, roll]` is the same as `from_euler_radians`.
+    pub fn from_euler_sequence(sequence: FusionEulerSequence, angles: [T; 3]) -> Self {
+        let axes = sequence_axes(sequence);
+        let rotation = |axis: usize, angle: T| {
+            let mut unit = [T::ZERO; 3];
+            unit[axis] = T::ONE;
+            Self::from_axis_angle(FusionVector::new(unit[0], unit[1], unit[2]), angle)
+        };
+        rotation(axes[0], angles[0]) * rotation(axes[1], angles[1]) * rotation(axes[2], angles[2])
+    }
+
+    /// Returns angles in radians of successive intrinsic rotations about the axes of sequence, the inverse of
+    /// `from_euler_sequence`. The first and third angles are from -pi to pi, the second is from -pi/2 to pi/2 for
+    /// Tait-Bryan sequences and from 0 to pi for proper Euler sequences. When gimbal locked, the third angle is zero.
+    /// # Examples
+    /// ```
+    /// use imu_fusion::{FusionEulerSequence, FusionQuaternion};
+    ///
+    /// let angles = [0.3f32, 1.2f32, -0.5f32];
+    /// let q = FusionQuaternion::from_euler_sequence(FusionEulerSequence::ZYZ, angles);
+    /// let result = q.euler_sequence(FusionEulerSequence::ZYZ);
+    /// # #[cfg(feature = "libm")] // micromath is less accurate
+    /// assert!((result[1] - 1.2f32).abs() < 1e-5);
+    /// ```
+    pub fn euler_sequence(&self, sequence: FusionEulerSequence) -> [T; 3] {
+        // Bernardes and Viollet, "Quaternion to Euler angles conversion: A direct, general and computationally
+        // efficient method", 2022. The intrinsic sequence is converted as the reversed extrinsic sequence.
+        let (angle_plus, angle_minus, middle, sign, proper) = self.sequence_terms(sequence);
+        let lock = T::from_f64(1e-6f64);
+        let (first, third) = if middle.abs() < lock {
+            (T::ZERO, T::TWO * angle_plus)
+        } else if (middle - T::PI).abs() < lock {
+            (T::ZERO, T::TWO * angle_minus)
+        } else {
+            (angle_plus - angle_minus, angle_plus + angle_minus)
+        };
+        let (middle, third) = if proper { (middle, third) } else { (middle - T::PI * T::HALF, third * sign) };
+        [wrap_angle(third), middle, wrap_angle(first)]
+    }
+
+    /// Returns true if the second angle of sequence is within tolerance radians of the value where the first and
+    /// third rotations are about the same axis, +/-pi/2 for Tait-Bryan sequences and 0 or pi for proper Euler sequences.
+    pub fn is_gimbal_locked(&self, sequence: FusionEulerSequence, tolerance: T) -> bool {
+        let (_, _, middle, _, _) = self.sequence_terms(sequence);
+        middle <= tolerance || T::PI - middle <= tolerance
+    }
+
+    // Returns the half sum and half difference of the first and third extrinsic angles, the middle angle before
+    // the Tait-Bryan offset, the permutation sign and whether the sequence is proper.
+    fn sequence_terms(&self, sequence: FusionEulerSequence) -> (T, T, T, T, bool) {
+        let q = [self.w, self.x, self.y, self.z];
+        let axes = sequence_axes(sequence);
+        let (i, j) = (axes[2] + 1, axes[1] + 1);
+        let proper = axes[0] == axes[2];
+        let k = if proper { 6 - i - j } else { axes[0] + 1 };
+        let sign = T::from_f64(((i as f64 - j as f64) * (j as f64 - k as f64) * (k as f64 - i as f64)) / 2.0f64);
+        let (a, b, c, d) = if proper {
+            (q[0], q[i], q[j], q[k] * sign)
+        } else {
+            (q[0] - q[j], q[i] + q[k] * sign, q[j] + q[0], q[k] * sign - q[i])
+        };
+        let middle = T::TWO * (c * c + d * d).sqrt().atan2((a * a + b * b).sqrt());
+        (b.atan2(a), d.atan2(c), middle, sign, proper)
+    }
+
+    pub fn rotation(self) -> FusionMatrix<T> {
         // calculate common terms to avoid repeated operations
         let qwqw = self.w * self.w;
         let qwqx = self.w * self.x;
@@ -56,21 +277,170 @@ impl FusionQuaternion {
         let qyqz = self.y * self.z;
         let qzqz = self.z * self.z;
         FusionMatrix {
-            xx: 2.0f32 * (qwqw - 0.5f32 + qxqx),
-            xy: 2.0f32 * (qxqy - qwqz),
-            xz: 2.0f32 * (qxqz + qwqy),
-            yx: 2.0f32 * (qxqy + qwqz),
-            yy: 2.0f32 * (qwqw - 0.5f32 + qyqy),
-            yz: 2.0f32 * (qyqz - qwqx),
-            zx: 2.0f32 * (qxqz - qwqy),
-            zy: 2.0f32 * (qyqz + qwqx),
-            zz: 2.0f32 * (qwqw - 0.5f32 + qzqz),
+            xx: T::TWO * (qwqw - T::HALF + qxqx),
+            xy: T::TWO * (qxqy - qwqz),
+            xz: T::TWO * (qxqz + qwqy),
+            yx: T::TWO * (qxqy + qwqz),
+            yy: T::TWO * (qwqw - T::HALF + qyqy),
+            yz: T::TWO * (qyqz - qwqx),
+            zx: T::TWO * (qxqz - qwqy),
+            zy: T::TWO * (qyqz + qwqx),
+            zz: T::TWO * (qwqw - T::HALF + qzqz),
+
+        }
+    }
+
+    /// Normalised linear interpolation from self (t = 0) to rhs (t = 1) along the shortest path.
+    pub fn nlerp(&self, rhs: &Self, t: T) -> Self {
+        let rhs = if self.dot(rhs) < T::ZERO { -*rhs } else { *rhs };
+        let q = *self * (T::ONE - t) + rhs * t;
+        q * (T::ONE / q.norm())
+    }
+
+    /// Spherical linear interpolation from self (t = 0) to rhs (t = 1) along the shortest path, at constant angular rate.
+    /// # Examples
+    /// ```
+    /// use imu_fusion::{FusionQuaternion, FusionVector};
+    ///
+    /// let z = FusionVector::new(0.0f32, 0.0f32, 1.0f32);
+    /// let a = FusionQuaternion::identity();
+    /// let b = FusionQuaternion::from_axis_angle(z, core::f32::consts::FRAC_PI_2);
+    /// let (_, angle) = a.slerp(&b, 0.5f32).to_axis_angle();
+    /// # #[cfg(feature = "libm")] // micromath is less accurate
+    /// assert!((angle - core::f32::consts::FRAC_PI_4).abs() < 1e-6);
+    /// ```
+    pub fn slerp(&self, rhs: &Self, t: T) -> Self {
+        let mut dot = self.dot(rhs);
+        let rhs = if dot < T::ZERO {
+            dot = -dot;
+            -*rhs
+        } else {
+            *rhs
+        };
+        // Nearly parallel, sin(angle) too small to divide by
+        if dot > T::from_f64(0.9995f64) {
+            return self.nlerp(&rhs, t);
+        }
+        let angle = (T::ONE - dot * dot).sqrt().atan2(dot);
+        let sin_angle = angle.sin();
+        *self * (((T::ONE - t) * angle).sin() / sin_angle) + rhs * ((t * angle).sin() / sin_angle)
+    }
+
+    /// Spherical cubic interpolation from q0 (t = 0) to q1 (t = 1) with control points from `squad_control_point`.
+    pub fn squad(q0: &Self, q1: &Self, a0: &Self, a1: &Self, t: T) -> Self {
+        q0.slerp(q1, t).slerp(&a0.slerp(a1, t), T::TWO * t * (T::ONE - t))
+    }
 
+    /// Returns the squad control point of current, given the previous and next quaternions of the sequence.
+    pub fn squad_control_point(previous: &Self, current: &Self, next: &Self) -> Self {
+        let inverse = current.conjugate();
+        let to_previous = (inverse * *previous).log();
+        let to_next = (inverse * *next).log();
+        *current * Self::exp((to_previous + to_next) * T::from_f64(-0.25f64))
+    }
+
+    /// Resamples quaternions with increasing timestamps in seconds at a new sample rate in Hz using slerp,
+    /// starting at the first timestamp. Returns the number of quaternions written to output.
+    pub fn resample(timestamps: &[T], quaternions: &[Self], sample_rate: T, output: &mut [Self]) -> usize {
+        let length = timestamps.len().min(quaternions.len());
+        if length == 0 {
+            return 0;
+        }
+        let mut index = 0;
+        for (count, sample) in output.iter_mut().enumerate() {
+            let timestamp = timestamps[0] + T::from_f64(count as f64) / sample_rate;
+            // Move past samples at or before the timestamp, so that repeated timestamps never give a zero-length interval
+            while index + 1 < length && timestamps[index + 1] <= timestamp {
+                index += 1;
+            }
+            if index + 1 == length {
+                if timestamp > timestamps[index] {
+                    return count;
+                }
+                *sample = quaternions[index];
+                continue;
+            }
+            let t = (timestamp - timestamps[index]) / (timestamps[index + 1] - timestamps[index]);
+            *sample = quaternions[index].slerp(&quaternions[index + 1], t);
         }
+        output.len()
+    }
+}
+
+// Returns the axis indices, 0 to 2 for X to Z, of the rotations of a sequence.
+fn sequence_axes(sequence: FusionEulerSequence) -> [usize; 3] {
+    match sequence {
+        FusionEulerSequence::XYZ => [0, 1, 2],
+        FusionEulerSequence::XZY => [0, 2, 1],
+        FusionEulerSequence::YXZ => [1, 0, 2],
+        FusionEulerSequence::YZX => [1, 2, 0],
+        FusionEulerSequence::ZXY => [2, 0, 1],
+        FusionEulerSequence::ZYX => [2, 1, 0],
+        FusionEulerSequence::XYX => [0, 1, 0],
+        FusionEulerSequence::XZX => [0, 2, 0],
+        FusionEulerSequence::YXY => [1, 0, 1],
+        FusionEulerSequence::YZY => [1, 2, 1],
+        FusionEulerSequence::ZXZ => [2, 0, 2],
+        FusionEulerSequence::ZYZ => [2, 1, 2],
+    }
+}
+
+// Wraps an angle from -2 pi to 2 pi to the range -pi to pi.
+fn wrap_angle<T: FusionFloat>(angle: T) -> T {
+    if angle > T::PI {
+        angle - T::TWO * T::PI
+    } else if angle < -T::PI {
+        angle + T::TWO * T::PI
+    } else {
+        angle
+    }
+}
+
+impl<T: FusionFloat> From<FusionMatrix<T>> for FusionQuaternion<T> {
+    /// Converts a rotation matrix to a unit quaternion, the inverse of `FusionQuaternion::rotation`.
+    /// Uses Shepperd's method, dividing by the largest of 4w², 4x², 4y² and 4z² to avoid loss of precision.
+    fn from(m: FusionMatrix<T>) -> Self {
+        let trace = m.xx + m.yy + m.zz;
+        let q = if trace > m.xx && trace > m.yy && trace > m.zz {
+            let s = T::TWO * (T::ONE + trace).sqrt();
+            Self { w: s / T::from_f64(4.0f64), x: (m.zy - m.yz) / s, y: (m.xz - m.zx) / s, z: (m.yx - m.xy) / s }
+        } else if m.xx > m.yy && m.xx > m.zz {
+            let s = T::TWO * (T::ONE + m.xx - m.yy - m.zz).sqrt();
+            Self { w: (m.zy - m.yz) / s, x: s / T::from_f64(4.0f64), y: (m.xy + m.yx) / s, z: (m.xz + m.zx) / s }
+        } else if m.yy > m.zz {
+            let s = T::TWO * (T::ONE + m.yy - m.xx - m.zz).sqrt();
+            Self { w: (m.xz - m.zx) / s, x: (m.xy + m.yx) / s, y: s / T::from_f64(4.0f64), z: (m.yz + m.zy) / s }
+        } else {
+            let s = T::TWO * (T::ONE + m.zz - m.xx - m.yy).sqrt();
+            Self { w: (m.yx - m.xy) / s, x: (m.xz + m.zx) / s, y: (m.yz + m.zy) / s, z: s / T::from_f64(4.0f64) }
+        };
+        // Correct for a rotation matrix that is not exactly orthonormal
+        q * (T::ONE / q.norm())
+    }
+}
+
+impl<T: FusionFloat> Default for FusionQuaternion<T> {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl<T: FusionFloat> fmt::Display for FusionQuaternion<T> {
+    /// Formats as (w, x, y, z), applying the precision to each component.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("(")?;
+        fmt::Display::fmt(&self.w, f)?;
+        f.write_str(", ")?;
+        fmt::Display::fmt(&self.x, f)?;
+        f.write_str(", ")?;
+        fmt::Display::fmt(&self.y, f)?;
+        f.write_str(", ")?;
+        fmt::Display::fmt(&self.z, f)?;
+        f.write_str(")")
     }
 }
 
-impl ops::Add for FusionQuaternion {
+impl<T: FusionFloat> ops::Add for FusionQuaternion<T> {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
@@ -83,7 +453,33 @@ impl ops::Add for FusionQuaternion {
     }
 }
 
-impl ops::Mul for FusionQuaternion {
+impl<T: FusionFloat> ops::Sub for FusionQuaternion<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self {
+            w: self.w - rhs.w,
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            z: self.z - rhs.z,
+        }
+    }
+}
+
+impl<T: FusionFloat> ops::Neg for FusionQuaternion<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            w: -self.w,
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
+impl<T: FusionFloat> ops::Mul for FusionQuaternion<T> {
     type Output = Self;
 
     fn mul(self, rhs: Self) -> Self::Output {
@@ -96,9 +492,9 @@ impl ops::Mul for FusionQuaternion {
     }
 }
 
-impl ops::Mul<FusionVector> for FusionQuaternion {
+impl<T: FusionFloat> ops::Mul<FusionVector<T>> for FusionQuaternion<T> {
     type Output = Self;
-    fn mul(self, rhs: FusionVector) -> Self::Output {
+    fn mul(self, rhs: FusionVector<T>) -> Self::Output {
         Self {
             w: -self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
             x: self.w * rhs.x + self.y * rhs.z - self.z * rhs.y,
@@ -108,9 +504,9 @@ impl ops::Mul<FusionVector> for FusionQuaternion {
     }
 }
 
-impl ops::Mul<f32> for FusionQuaternion {
+impl<T: FusionFloat> ops::Mul<T> for FusionQuaternion<T> {
     type Output = Self;
-    fn mul(self, rhs: f32) -> Self::Output {
+    fn mul(self, rhs: T) -> Self::Output {
         Self {
             w: self.w * rhs,
             x: self.x * rhs,
@@ -120,3 +516,147 @@ impl ops::Mul<f32> for FusionQuaternion {
     }
 }
 
+
+impl<T: FusionFloat> ops::Div for FusionQuaternion<T> {
+    type Output = Self;
+    // Right division, self * rhs^-1
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self::Output {
+        self * rhs.inverse()
+    }
+}
+
+#[cfg(feature = "libm")]
+#[test]
+fn algebra_test() {
+    use core::f64::consts::{FRAC_PI_2, PI};
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+    let x = FusionVector::new(1.0f64, 0.0f64, 0.0f64);
+    let z = FusionVector::new(0.0f64, 0.0f64, 1.0f64);
+
+    // 90 degrees about Z maps X to Y
+    let q = FusionQuaternion::from_axis_angle(z * 2.0f64, FRAC_PI_2);
+    let v = q.rotate_vector(x);
+    assert!(close(v.x, 0.0f64) && close(v.y, 1.0f64) && close(v.z, 0.0f64));
+    let m = q.rotation() * x;
+    assert!(close(m.x, v.x) && close(m.y, v.y) && close(m.z, v.z));
+    let (axis, angle) = q.to_axis_angle();
+    assert!(close(axis.z, 1.0f64) && close(angle, FRAC_PI_2));
+
+    // Inverse, conjugate and division
+    let p = FusionQuaternion { w: 1.0f64, x: 2.0f64, y: 3.0f64, z: 4.0f64 };
+    let identity = p * p.inverse();
+    assert!(close(identity.w, 1.0f64) && close(identity.x, 0.0f64) && close(identity.y, 0.0f64) && close(identity.z, 0.0f64));
+    let back = (p * q) / q;
+    assert!(close(back.w, p.w) && close(back.x, p.x) && close(back.y, p.y) && close(back.z, p.z));
+    assert!(close(p.norm_squared(), 30.0f64) && close(p.conjugate().dot(&p), -28.0f64));
+    assert!(close((p - p).norm(), 0.0f64) && close((-p).w, -1.0f64));
+
+    // Angle between, including the double cover
+    let r = FusionQuaternion::from_axis_angle(x, PI / 3.0f64);
+    assert!(close(q.angle_between(&(q * r)), PI / 3.0f64));
+    assert!(close(q.angle_between(&-(q * r)), PI / 3.0f64));
+
+    // Exponential and logarithmic maps
+    let rotation_vector = FusionVector::new(0.3f64, -0.2f64, 0.1f64);
+    let log = FusionQuaternion::exp(rotation_vector).log();
+    assert!(close(log.x, 0.3f64) && close(log.y, -0.2f64) && close(log.z, 0.1f64));
+    let log = (-FusionQuaternion::exp(rotation_vector)).log();
+    assert!(close(log.x, 0.3f64) && close(log.y, -0.2f64) && close(log.z, 0.1f64));
+    let small = FusionQuaternion::exp(FusionVector::new(1e-6f64, 0.0f64, 0.0f64));
+    assert!(close(small.x, 5e-7f64) && close(small.log().x, 1e-6f64));
+}
+
+#[cfg(feature = "libm")]
+#[test]
+fn interpolation_test() {
+    use core::f64::consts::FRAC_PI_2;
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+    let z = FusionVector::new(0.0f64, 0.0f64, 1.0f64);
+    let a = FusionQuaternion::from_axis_angle(z, 0.1f64);
+    let b = FusionQuaternion::from_axis_angle(z, 0.1f64 + FRAC_PI_2);
+
+    // Constant angular rate, and the same result for -b
+    for t in [0.0f64, 0.25f64, 0.5f64, 1.0f64] {
+        assert!(close(a.angle_between(&a.slerp(&b, t)), FRAC_PI_2 * t));
+        assert!(close(a.angle_between(&a.slerp(&-b, t)), FRAC_PI_2 * t));
+    }
+    let nlerp = a.nlerp(&-b, 0.5f64);
+    assert!(close(nlerp.norm(), 1.0f64) && close(a.angle_between(&nlerp), FRAC_PI_2 * 0.5f64));
+
+    // Squad reduces to slerp for rotations about one axis
+    let c = FusionQuaternion::from_axis_angle(z, 0.1f64 + 2.0f64 * FRAC_PI_2);
+    let a1 = FusionQuaternion::squad_control_point(&FusionQuaternion::from_axis_angle(z, 0.1f64 - FRAC_PI_2), &a, &b);
+    let b1 = FusionQuaternion::squad_control_point(&a, &b, &c);
+    assert!(close(a.angle_between(&FusionQuaternion::squad(&a, &b, &a1, &b1, 0.3f64)), FRAC_PI_2 * 0.3f64));
+
+    // Resample 3 samples at 1 Hz to 4 Hz
+    let mut output = [FusionQuaternion::identity(); 16];
+    let count = FusionQuaternion::resample(&[0.0f64, 1.0f64, 2.0f64], &[a, b, c], 4.0f64, &mut output);
+    assert_eq!(count, 9);
+    assert!(close(a.angle_between(&output[6]), FRAC_PI_2 * 1.5f64));
+    assert!(close(c.angle_between(&output[8]), 0.0f64));
+}
+
+#[cfg(feature = "libm")]
+#[test]
+fn resample_repeated_timestamp_test() {
+    let z = FusionVector::new(0.0f64, 0.0f64, 1.0f64);
+    let a = FusionQuaternion::from_axis_angle(z, 0.1f64);
+    let b = FusionQuaternion::from_axis_angle(z, 0.5f64);
+    let c = FusionQuaternion::from_axis_angle(z, 1.0f64);
+    let mut output = [FusionQuaternion::identity(); 8];
+    let count = FusionQuaternion::resample(&[0.0f64, 0.0f64, 1.0f64, 1.0f64], &[a, b, c, c], 2.0f64, &mut output);
+    assert_eq!(count, 3);
+    assert!(output[..count].iter().all(|q| q.is_finite()));
+    // The later of the repeated samples is used
+    assert!(b.angle_between(&output[0]) < 1e-9f64);
+    assert!(c.angle_between(&output[2]) < 1e-9f64);
+}
+
+#[cfg(feature = "libm")]
+#[test]
+fn euler_sequence_test() {
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+    let sequences = [
+        FusionEulerSequence::XYZ, FusionEulerSequence::XZY, FusionEulerSequence::YXZ,
+        FusionEulerSequence::YZX, FusionEulerSequence::ZXY, FusionEulerSequence::ZYX,
+        FusionEulerSequence::XYX, FusionEulerSequence::XZX, FusionEulerSequence::YXY,
+        FusionEulerSequence::YZY, FusionEulerSequence::ZXZ, FusionEulerSequence::ZYZ,
+    ];
+    for sequence in sequences {
+        for angles in [[0.3f64, 0.4f64, -0.5f64], [-2.5f64, 1.1f64, 3.0f64], [1.0f64, 0.0f64, 0.0f64]] {
+            let q = FusionQuaternion::from_euler_sequence(sequence, angles);
+            let result = q.euler_sequence(sequence);
+            assert!(q.angle_between(&FusionQuaternion::from_euler_sequence(sequence, result)) < 1e-9);
+            assert!(!q.is_gimbal_locked(sequence, 0.01f64) || sequence as usize > 5);
+        }
+        let result = FusionQuaternion::from_euler_sequence(sequence, [0.3f64, 0.4f64, -0.5f64]).euler_sequence(sequence);
+        assert!(close(result[0], 0.3f64) && close(result[1], 0.4f64) && close(result[2], -0.5f64));
+        // Gimbal lock keeps the rotation and reports it
+        let middle = if (sequence as usize) < 6 { core::f64::consts::FRAC_PI_2 } else { 0.0f64 };
+        let q = FusionQuaternion::from_euler_sequence(sequence, [0.3f64, middle, 0.2f64]);
+        let result = q.euler_sequence(sequence);
+        assert!(q.is_gimbal_locked(sequence, 0.01f64));
+        assert!(close(result[2], 0.0f64));
+        assert!(q.angle_between(&FusionQuaternion::from_euler_sequence(sequence, result)) < 1e-9);
+    }
+
+    // ZYX matches the roll, pitch and yaw of euler
+    let euler = FusionEuler { angle: Angle { roll: 10.0f64, pitch: -20.0f64, yaw: 30.0f64 } };
+    let q = FusionQuaternion::from_euler(euler);
+    let angles = q.euler_sequence(FusionEulerSequence::ZYX);
+    assert!(close(fusion_radians_to_degrees(angles[0]), 30.0f64) && close(fusion_radians_to_degrees(angles[1]), -20.0f64));
+    assert!(close(q.euler().angle.roll, 10.0f64) && !q.euler().is_gimbal_locked(1.0f64));
+}
+
+#[test]
+fn default_test() {
+    let q = FusionQuaternion::<f32>::default();
+    assert_eq!(q, FusionQuaternion::identity());
+    assert!(q.approx_eq(&FusionQuaternion { w: 0.9999f32, x: 0.0001f32, y: 0.0f32, z: 0.0f32 }, 1e-3f32));
+    assert!(!q.approx_eq(&-q, 1e-3f32));
+    assert_eq!(FusionMatrix::<f32>::default(), FusionMatrix::identity());
+    assert_eq!(FusionEuler::<f32>::default(), FusionEuler::zero());
+    assert!(FusionVector::<f32>::default().is_zero());
+}
@@ -0,0 +1,76 @@
+use crate::FusionTimestamp;
+
+impl FusionTimestamp {
+    pub fn as_secs_f64(&self) -> f64 {
+        match *self {
+            FusionTimestamp::Seconds(seconds) => seconds,
+            FusionTimestamp::Microseconds(microseconds) => microseconds as f64 / 1e6f64,
+        }
+    }
+
+    /// Returns the time in seconds elapsed since an earlier timestamp, negative if the earlier timestamp is later.
+    pub fn seconds_since(&self, earlier: FusionTimestamp) -> f64 {
+        match (*self, earlier) {
+            (FusionTimestamp::Microseconds(microseconds), FusionTimestamp::Microseconds(earlier)) => {
+                // Integer difference keeps microsecond precision regardless of uptime
+                microseconds.wrapping_sub(earlier) as i64 as f64 / 1e6f64
+            }
+            _ => self.as_secs_f64() - earlier.as_secs_f64(),
+        }
+    }
+
+    /// Extends a wrapping 32-bit microsecond tick counter into a 64-bit timestamp following this one.
+    ///
+    /// The counter is assumed to have moved by less than 2^31 microseconds (about 35 minutes) since this timestamp,
+    /// so a counter that steps backwards gives an earlier timestamp instead of a wrap.
+    /// A timestamp in seconds is converted to microseconds first, so the counter is taken to be in the same time base.
+    pub fn following_micros_u32(&self, timestamp_us: u32) -> FusionTimestamp {
+        let last = match *self {
+            FusionTimestamp::Microseconds(last) => last,
+            // Negative seconds saturate to zero
+            FusionTimestamp::Seconds(seconds) => (seconds * 1e6f64 + 0.5f64) as u64,
+        };
+        let elapsed = timestamp_us.wrapping_sub(last as u32) as i32;
+        FusionTimestamp::Microseconds(last.wrapping_add_signed(elapsed as i64))
+    }
+}
+
+#[test]
+fn seconds_since_test() {
+    // 30 years of uptime in microseconds
+    let earlier = FusionTimestamp::Microseconds(946_080_000_000_000u64);
+    let later = FusionTimestamp::Microseconds(946_080_000_010_000u64);
+    assert_eq!(later.seconds_since(earlier), 0.01f64);
+    assert_eq!(earlier.seconds_since(later), -0.01f64);
+    assert_eq!(FusionTimestamp::Seconds(2.5f64).seconds_since(FusionTimestamp::Microseconds(500_000u64)), 2.0f64);
+}
+
+#[test]
+fn following_micros_u32_test() {
+    let last = FusionTimestamp::Microseconds((u32::MAX - 4_999) as u64);
+    let next = last.following_micros_u32(5_000);
+    assert_eq!(next.seconds_since(last), 0.01f64);
+    match next {
+        FusionTimestamp::Microseconds(microseconds) => assert_eq!(microseconds, u32::MAX as u64 + 5_001),
+        FusionTimestamp::Seconds(_) => panic!("expected microseconds"),
+    }
+}
+
+#[test]
+fn following_micros_u32_backwards_test() {
+    let last = FusionTimestamp::Microseconds(10_000u64);
+    let next = last.following_micros_u32(9_000);
+    assert_eq!(next.seconds_since(last), -0.001f64);
+    // Stepping back across the rollover is also a small negative step
+    let last = FusionTimestamp::Microseconds((u32::MAX - 4_999) as u64).following_micros_u32(5_000);
+    let next = last.following_micros_u32(u32::MAX - 4_999);
+    assert_eq!(next.seconds_since(last), -0.01f64);
+}
+
+#[test]
+fn following_micros_u32_after_seconds_test() {
+    // The counter has wrapped once since the clock was at zero
+    let last = FusionTimestamp::Seconds(4_295.0f64);
+    let next = last.following_micros_u32(42_704);
+    assert_eq!(next, FusionTimestamp::Microseconds(4_295_010_000u64));
+}
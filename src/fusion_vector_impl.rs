@@ -1,16 +1,14 @@
-use core::ops;
-#[cfg(feature = "fusion-use-normal-sqrt")]
-use libm::sqrtf;
+use core::{fmt, ops};
 
-use crate::{fusion_fast_inverse_sqrt, FusionVector};
+use crate::{FusionFloat, FusionVector};
 
-impl FusionVector {
-    pub fn get(&self, x: &mut f32, y: &mut f32, z: &mut f32) {
+impl<T: FusionFloat> FusionVector<T> {
+    pub fn get(&self, x: &mut T, y: &mut T, z: &mut T) {
         *x = self.x;
         *y = self.y;
         *z = self.z;
     }
-    pub fn new(x: f32, y: f32, z: f32) -> Self {
+    pub fn new(x: T, y: T, z: T) -> Self {
         Self {
             x,
             y,
@@ -18,29 +16,29 @@ impl FusionVector {
         }
     }
     pub fn zero() -> Self {
-        const VALUE: FusionVector =
-            FusionVector {
-                x: 0.0f32,
-                y: 0.0f32,
-                z: 0.0f32,
-            };
-        VALUE
+        Self {
+            x: T::ZERO,
+            y: T::ZERO,
+            z: T::ZERO,
+        }
     }
 
     pub fn ones() -> Self {
-        const VALUE: FusionVector =
-            FusionVector {
-                x: 1.0f32,
-                y: 1.0f32,
-                z: 1.0f32,
-            };
-        VALUE
+        Self {
+            x: T::ONE,
+            y: T::ONE,
+            z: T::ONE,
+        }
     }
 
     pub fn is_zero(&self) -> bool {
-        self.x == 0.0f32 && self.y == 0.0f32 && self.z == 0.0f32
+        self.x == T::ZERO && self.y == T::ZERO && self.z == T::ZERO
     }
-    pub fn sum(&self) -> f32 {
+
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.z.is_finite()
+    }
+    pub fn sum(&self) -> T {
         self.x + self.y + self.z
     }
 
@@ -52,31 +50,108 @@ impl FusionVector {
         }
     }
 
-    pub fn dot_product(&self, rhs: &Self) -> f32 {
+    pub fn dot_product(&self, rhs: &Self) -> T {
         self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
     }
 
-    pub fn magnitude_squared(&self, rhs: &Self) -> f32 {
-        self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+    #[deprecated(note = "returns the dot product, use `dot_product` or `norm_squared`")]
+    pub fn magnitude_squared(&self, rhs: &Self) -> T {
+        self.dot_product(rhs)
+    }
+
+    #[deprecated(note = "returns the squared magnitude, use `norm_squared` or `norm`")]
+    pub fn magnitude(&self) -> T {
+        self.norm_squared()
     }
 
-    pub fn magnitude(&self) -> f32 {
+    pub fn norm_squared(&self) -> T {
         self.x * self.x + self.y * self.y + self.z * self.z
     }
 
+    pub fn norm(&self) -> T {
+        self.norm_squared().sqrt()
+    }
+
+    /// Returns the unit vector, or the zero vector if the vector is zero.
     pub fn normalize(&self) -> Self {
-        #[cfg(feature = "fusion-use-normal-sqrt")]
-        {
-            *self * (1.0f32 / sqrtf(self.magnitude()))
+        if self.is_zero() {
+            return Self::zero();
         }
-        #[cfg(not(feature = "fusion-use-normal-sqrt"))]
-        {
-            *self * fusion_fast_inverse_sqrt(self.magnitude())
+        *self * self.norm_squared().inverse_sqrt()
+    }
+
+    /// Returns the angle in radians between two vectors, from 0 to pi.
+    pub fn angle_between(&self, rhs: &Self) -> T {
+        self.cross_product(rhs).norm().atan2(self.dot_product(rhs))
+    }
+
+    /// Returns the component of the vector in the direction of rhs, or the zero vector if rhs is zero.
+    pub fn project_onto(&self, rhs: &Self) -> Self {
+        let rhs_norm_squared = rhs.norm_squared();
+        if rhs_norm_squared == T::ZERO {
+            return Self::zero();
         }
+        *rhs * (self.dot_product(rhs) / rhs_norm_squared)
+    }
+
+    /// Linear interpolation from self (t = 0) to rhs (t = 1).
+    pub fn lerp(&self, rhs: &Self, t: T) -> Self {
+        *self + (*rhs - *self) * t
+    }
+
+    pub fn abs(&self) -> Self {
+        Self::new(self.x.abs(), self.y.abs(), self.z.abs())
+    }
+
+    pub fn min(&self, rhs: &Self) -> Self {
+        let min = |a: T, b: T| if b < a { b } else { a };
+        Self::new(min(self.x, rhs.x), min(self.y, rhs.y), min(self.z, rhs.z))
+    }
+
+    pub fn max(&self, rhs: &Self) -> Self {
+        let max = |a: T, b: T| if b > a { b } else { a };
+        Self::new(max(self.x, rhs.x), max(self.y, rhs.y), max(self.z, rhs.z))
+    }
+
+    /// Returns true if each component differs by at most epsilon.
+    pub fn approx_eq(&self, rhs: &Self, epsilon: T) -> bool {
+        let difference = (*self - *rhs).abs();
+        difference.x <= epsilon && difference.y <= epsilon && difference.z <= epsilon
+    }
+
+    /// Limits each component to the range of the same component of min and max.
+    pub fn clamp(&self, min: &Self, max: &Self) -> Self {
+        self.max(min).min(max)
+    }
+}
+
+impl<T: FusionFloat> Default for FusionVector<T> {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+impl<T: FusionFloat> fmt::Display for FusionVector<T> {
+    /// Formats as (x, y, z), applying the precision to each component.
+    /// # Examples
+    /// ```
+    /// use imu_fusion::FusionVector;
+    ///
+    /// let v = FusionVector::new(1.0f32, -0.5f32, 0.25f32);
+    /// assert_eq!(format!("{:.2}", v), "(1.00, -0.50, 0.25)");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("(")?;
+        fmt::Display::fmt(&self.x, f)?;
+        f.write_str(", ")?;
+        fmt::Display::fmt(&self.y, f)?;
+        f.write_str(", ")?;
+        fmt::Display::fmt(&self.z, f)?;
+        f.write_str(")")
     }
 }
 
-impl ops::Add for FusionVector {
+impl<T: FusionFloat> ops::Add for FusionVector<T> {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
@@ -88,7 +163,7 @@ impl ops::Add for FusionVector {
     }
 }
 
-impl ops::Sub for FusionVector {
+impl<T: FusionFloat> ops::Sub for FusionVector<T> {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self::Output {
@@ -100,7 +175,7 @@ impl ops::Sub for FusionVector {
     }
 }
 
-impl ops::AddAssign for FusionVector {
+impl<T: FusionFloat> ops::AddAssign for FusionVector<T> {
     fn add_assign(&mut self, rhs: Self) {
         self.x += rhs.x;
         self.y += rhs.y;
@@ -108,7 +183,7 @@ impl ops::AddAssign for FusionVector {
     }
 }
 
-impl ops::SubAssign for FusionVector {
+impl<T: FusionFloat> ops::SubAssign for FusionVector<T> {
     fn sub_assign(&mut self, rhs: Self) {
         self.x -= rhs.x;
         self.y -= rhs.y;
@@ -116,7 +191,7 @@ impl ops::SubAssign for FusionVector {
     }
 }
 
-impl ops::Mul for FusionVector {
+impl<T: FusionFloat> ops::Mul for FusionVector<T> {
     type Output = Self;
 
     fn mul(self, rhs: Self) -> Self::Output {
@@ -128,10 +203,10 @@ impl ops::Mul for FusionVector {
     }
 }
 
-impl ops::Mul<f32> for FusionVector {
+impl<T: FusionFloat> ops::Mul<T> for FusionVector<T> {
     type Output = Self;
 
-    fn mul(self, rhs: f32) -> Self::Output {
+    fn mul(self, rhs: T) -> Self::Output {
         Self {
             x: self.x * rhs,
             y: self.y * rhs,
@@ -140,7 +215,7 @@ impl ops::Mul<f32> for FusionVector {
     }
 }
 
-impl ops::MulAssign for FusionVector {
+impl<T: FusionFloat> ops::MulAssign for FusionVector<T> {
     fn mul_assign(&mut self, rhs: Self) {
         self.x *= rhs.x;
         self.y *= rhs.y;
@@ -148,6 +223,70 @@ impl ops::MulAssign for FusionVector {
     }
 }
 
+impl<T: FusionFloat> ops::MulAssign<T> for FusionVector<T> {
+    fn mul_assign(&mut self, rhs: T) {
+        self.x *= rhs;
+        self.y *= rhs;
+        self.z *= rhs;
+    }
+}
+
+impl<T: FusionFloat> ops::Div<T> for FusionVector<T> {
+    type Output = Self;
+
+    fn div(self, rhs: T) -> Self::Output {
+        Self {
+            x: self.x / rhs,
+            y: self.y / rhs,
+            z: self.z / rhs,
+        }
+    }
+}
+
+impl<T: FusionFloat> ops::DivAssign<T> for FusionVector<T> {
+    fn div_assign(&mut self, rhs: T) {
+        self.x /= rhs;
+        self.y /= rhs;
+        self.z /= rhs;
+    }
+}
+
+impl<T: FusionFloat> ops::Neg for FusionVector<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
+impl<T: FusionFloat> ops::Index<usize> for FusionVector<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match index {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("FusionVector index out of range: {}", index),
+        }
+    }
+}
+
+impl<T: FusionFloat> ops::IndexMut<usize> for FusionVector<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        match index {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            2 => &mut self.z,
+            _ => panic!("FusionVector index out of range: {}", index),
+        }
+    }
+}
+
 #[test]
 fn add_test() {
     let a = FusionVector {
@@ -164,4 +303,24 @@ fn add_test() {
     assert_eq!(c.x, 5.0f32);
     assert_eq!(c.y, 7.0f32);
     assert_eq!(c.z, 9.0f32);
-}
\ No newline at end of file
+}
+#[cfg(feature = "libm")]
+#[test]
+fn norm_test() {
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+    let mut a = FusionVector::new(3.0f64, 0.0f64, 4.0f64);
+    assert!(close(a.norm(), 5.0f64) && close(a.norm_squared(), 25.0f64));
+    assert!(FusionVector::<f32>::zero().normalize().is_zero());
+    let b = FusionVector::new(0.0f64, 2.0f64, 0.0f64);
+    assert!(close(a.angle_between(&b), core::f64::consts::FRAC_PI_2));
+    assert!(close(a.project_onto(&FusionVector::new(2.0f64, 0.0f64, 0.0f64)).x, 3.0f64));
+    assert!(a.project_onto(&FusionVector::zero()).is_zero());
+    assert!(close(a.lerp(&b, 0.5f64).y, 1.0f64) && close((-a / 2.0f64).z, -2.0f64));
+    let clamped = FusionVector::new(-5.0f64, 0.5f64, 5.0f64).clamp(&-FusionVector::ones(), &FusionVector::ones());
+    assert!(close(clamped.x, -1.0f64) && close(clamped.y, 0.5f64) && close(clamped.z, 1.0f64));
+    assert!(close(FusionVector::new(-1.0f64, 2.0f64, -3.0f64).abs().sum(), 6.0f64));
+    a /= 5.0f64;
+    a *= 10.0f64;
+    a[1] = 1.0f64;
+    assert!(close(a[0], 6.0f64) && close(a[1], 1.0f64) && close(a[2], 8.0f64));
+}
@@ -0,0 +1,77 @@
+//! Conversions between the Fusion math types and glam, `Vec3`, `Quat` and `Mat3` for f32 and `DVec3`, `DQuat` and
+//! `DMat3` for f64, the latter with the `libm` feature.
+//!
+//! Both use Hamilton quaternions, so `Quat::from(fusion.quaternion()) * v` is the same as
+//! `FusionQuaternion::rotate_vector`. glam matrices are stored by column, `Mat3::from(m).x_axis` is the first column
+//! `(m.xx, m.yx, m.zx)`.
+use crate::{FusionMatrix, FusionQuaternion, FusionVector};
+use glam::{Mat3, Quat, Vec3};
+#[cfg(feature = "libm")]
+use glam::{DMat3, DQuat, DVec3};
+
+macro_rules! impl_glam {
+    ($t:ty, $vec3:ty, $quat:ty, $mat3:ty) => {
+        impl From<$vec3> for FusionVector<$t> {
+            fn from(v: $vec3) -> Self {
+                Self::new(v.x, v.y, v.z)
+            }
+        }
+
+        impl From<FusionVector<$t>> for $vec3 {
+            fn from(v: FusionVector<$t>) -> Self {
+                <$vec3>::new(v.x, v.y, v.z)
+            }
+        }
+
+        impl From<$quat> for FusionQuaternion<$t> {
+            fn from(q: $quat) -> Self {
+                let [x, y, z, w] = q.to_array();
+                Self { w, x, y, z }
+            }
+        }
+
+        impl From<FusionQuaternion<$t>> for $quat {
+            fn from(q: FusionQuaternion<$t>) -> Self {
+                <$quat>::from_xyzw(q.x, q.y, q.z, q.w)
+            }
+        }
+
+        impl From<$mat3> for FusionMatrix<$t> {
+            fn from(m: $mat3) -> Self {
+                Self::new(m.x_axis.x, m.y_axis.x, m.z_axis.x, m.x_axis.y, m.y_axis.y, m.z_axis.y, m.x_axis.z, m.y_axis.z, m.z_axis.z)
+            }
+        }
+
+        impl From<FusionMatrix<$t>> for $mat3 {
+            fn from(m: FusionMatrix<$t>) -> Self {
+                <$mat3>::from_cols(<$vec3>::new(m.xx, m.yx, m.zx), <$vec3>::new(m.xy, m.yy, m.zy), <$vec3>::new(m.xz, m.yz, m.zz))
+            }
+        }
+    };
+}
+
+impl_glam!(f32, Vec3, Quat, Mat3);
+#[cfg(feature = "libm")]
+impl_glam!(f64, DVec3, DQuat, DMat3);
+
+#[cfg(feature = "libm")]
+#[test]
+fn conversion_test() {
+    let q = FusionQuaternion::from_axis_angle(FusionVector::new(1.0f32, -2.0f32, 0.5f32), 0.8f32);
+    let v = FusionVector::new(0.3f32, 0.2f32, -1.0f32);
+
+    // Exact round trips
+    assert_eq!(FusionQuaternion::from(Quat::from(q)), q);
+    assert_eq!(FusionVector::from(Vec3::from(v)), v);
+    let m = FusionMatrix::from([[1.0f64, 2.0f64, 3.0f64], [4.0f64, 5.0f64, 6.0f64], [7.0f64, 8.0f64, 9.0f64]]);
+    assert_eq!(FusionMatrix::from(DMat3::from(m)), m);
+
+    // Row and column order
+    assert_eq!(DMat3::from(m).x_axis, DVec3::new(1.0f64, 4.0f64, 7.0f64));
+    assert_eq!(DMat3::from(m) * DVec3::X, DVec3::new(1.0f64, 4.0f64, 7.0f64));
+
+    // Same rotation
+    let expected = q.rotate_vector(v);
+    assert!(FusionVector::from(Quat::from(q) * Vec3::from(v)).approx_eq(&expected, 1e-6f32));
+    assert!(FusionMatrix::from(Mat3::from_quat(Quat::from(q))).approx_eq(&q.rotation(), 1e-6f32));
+}
@@ -1,5 +1,9 @@
 #![no_std]
 
+#[cfg(not(any(feature = "libm", feature = "micromath")))]
+compile_error!("either the libm or the micromath feature must be enabled");
+
+mod fusion_float_impl;
 mod fusion_vector_impl;
 mod fusion_quaternion_impl;
 mod fusion_matrix_impl;
@@ -7,10 +11,29 @@ mod fusion_euler_impl;
 mod fusion_impl;
 mod fusion_ahrs_impl;
 mod fusion_gyr_offset_impl;
+mod fusion_gyr_temp_offset_impl;
+mod fusion_madgwick_impl;
+mod fusion_mahony_impl;
+mod fusion_timestamp_impl;
+mod fusion_dt_policy_impl;
+#[cfg(feature = "cgmath")]
+mod cgmath;
+#[cfg(feature = "glam")]
+mod glam;
+#[cfg(feature = "mint")]
+mod mint;
+#[cfg(feature = "nalgebra")]
 mod nalgebra;
+pub mod axes;
+pub mod compass;
+
+use core::{fmt, ops};
 
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FusionConvention {
     /* North-West-Up */
+    #[default]
     NWU,
     /* East-North-Up */
     ENU,
@@ -18,30 +41,196 @@ pub enum FusionConvention {
     NED,
 }
 
-pub struct Fusion {
-    pub gyr_misalignment: FusionMatrix,
-    pub gyr_sensitivity: FusionVector,
-    pub gyr_offset: FusionVector,
-    pub acc_misalignment: FusionMatrix,
-    pub acc_sensitivity: FusionVector,
-    pub acc_offset: FusionVector,
-    pub soft_iron_matrix: FusionMatrix,
-    pub hard_iron_offset: FusionVector,
-    pub ahrs: FusionAhrs,
-    pub offset: FusionGyrOffset,
-    pub last_timestamp: f32,
-}
-
-pub struct FusionAhrs {
-    pub settings: FusionAhrsSettings,
-    pub quaternion: FusionQuaternion,
-    pub acc: FusionVector,
+/* Axes of successive intrinsic rotations, e.g. ZYX is yaw about Z, then pitch about the new Y, then roll about the new X */
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FusionEulerSequence {
+    /* Tait-Bryan sequences */
+    XYZ,
+    XZY,
+    YXZ,
+    YZX,
+    ZXY,
+    ZYX,
+    /* Proper Euler sequences */
+    XYX,
+    XZX,
+    YXY,
+    YZY,
+    ZXZ,
+    ZYZ,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FusionAxesAlignment {
+    /* +X+Y+Z */
+    #[default]
+    PXPYPZ,
+    /* +X-Z+Y */
+    PXNZPY,
+    /* +X-Y-Z */
+    PXNYNZ,
+    /* +X+Z-Y */
+    PXPZNY,
+    /* -X+Y-Z */
+    NXPYNZ,
+    /* -X+Z+Y */
+    NXPZPY,
+    /* -X-Y+Z */
+    NXNYPZ,
+    /* -X-Z-Y */
+    NXNZNY,
+    /* +Y-X+Z */
+    PYNXPZ,
+    /* +Y-Z-X */
+    PYNZNX,
+    /* +Y+X-Z */
+    PYPXNZ,
+    /* +Y+Z+X */
+    PYPZPX,
+    /* -Y+X+Z */
+    NYPXPZ,
+    /* -Y-Z+X */
+    NYNZPX,
+    /* -Y-X-Z */
+    NYNXNZ,
+    /* -Y+Z-X */
+    NYPZNX,
+    /* +Z+Y-X */
+    PZPYNX,
+    /* +Z+X+Y */
+    PZPXPY,
+    /* +Z-Y+X */
+    PZNYPX,
+    /* +Z-X-Y */
+    PZNXNY,
+    /* -Z+Y+X */
+    NZPYPX,
+    /* -Z-X+Y */
+    NZNXPY,
+    /* -Z-Y-X */
+    NZNYNX,
+    /* -Z+X-Y */
+    NZPXNY,
+}
+
+/// Floating point scalar used by all fusion types, implemented for `f32` and `f64`.
+pub trait FusionFloat:
+    Copy
+    + PartialOrd
+    + ops::Add<Output = Self>
+    + ops::Sub<Output = Self>
+    + ops::Mul<Output = Self>
+    + ops::Div<Output = Self>
+    + ops::Neg<Output = Self>
+    + ops::AddAssign
+    + ops::SubAssign
+    + ops::MulAssign
+    + ops::DivAssign
+    + fmt::Debug
+    + fmt::Display
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const HALF: Self;
+    const TWO: Self;
+    const PI: Self;
+    const MAX: Self;
+
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+    fn is_finite(self) -> bool;
+    fn abs(self) -> Self;
+    fn sqrt(self) -> Self;
+    fn inverse_sqrt(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn asin(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FusionTimestamp {
+    /* Seconds */
+    Seconds(f64),
+    /* Microseconds */
+    Microseconds(u64),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fusion<T: FusionFloat = f32, F: OrientationFilter<T> = FusionAhrs<T>> {
+    pub gyr_misalignment: FusionMatrix<T>,
+    pub gyr_sensitivity: FusionVector<T>,
+    pub gyr_offset: FusionVector<T>,
+    pub acc_misalignment: FusionMatrix<T>,
+    pub acc_sensitivity: FusionVector<T>,
+    pub acc_offset: FusionVector<T>,
+    pub soft_iron_matrix: FusionMatrix<T>,
+    pub hard_iron_offset: FusionVector<T>,
+    pub gyr_alignment: FusionAxesAlignment,
+    pub acc_alignment: FusionAxesAlignment,
+    pub mag_alignment: FusionAxesAlignment,
+    pub ahrs: F,
+    /* Built-in gyroscope offset correction, bypassed when temp_offset is set */
+    pub offset: FusionGyrOffset<T>,
+    /* Temperature-compensated gyroscope offset correction used instead of offset, see Fusion::set_temperature */
+    pub temp_offset: Option<FusionGyrTempOffset<T>>,
+    /* Gyroscope temperature in degrees Celsius passed to temp_offset */
+    pub temperature: T,
+    pub sample_rate: u32,
+    pub dt_policy: FusionDtPolicy<T>,
+    pub last_timestamp: Option<FusionTimestamp>,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FusionDtPolicy<T: FusionFloat = f32> {
+    pub skip_first_sample: bool,
+    pub max_dt: T,
+    pub reject_non_monotonic: bool,
+    pub nominal_fallback: bool,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FusionDtStatus {
+    /* Measured delta time used */
+    Measured,
+    /* First sample only initialised the timestamp, update skipped */
+    FirstSampleSkipped,
+    /* Delta time exceeded the maximum and was clamped */
+    Clamped,
+    /* Timestamp did not increase, update skipped */
+    NonMonotonicRejected,
+    /* Nominal delta time of 1 / sample rate used instead of the measured one */
+    NominalFallback,
+}
+
+/// Orientation estimation algorithm driven by `Fusion` after axes alignment, calibration and gyroscope offset correction.
+pub trait OrientationFilter<T: FusionFloat = f32> {
+    /// Updates the orientation with gyroscope data in degrees/s, accelerometer data in g, optional magnetometer data in
+    /// arbitrary units and the delta time in seconds.
+    fn update(&mut self, gyr: FusionVector<T>, acc: FusionVector<T>, mag: Option<FusionVector<T>>, delta_t: T);
+    /// Returns the orientation of the sensor relative to the earth.
+    fn quaternion(&self) -> FusionQuaternion<T>;
+    fn set_quaternion(&mut self, quaternion: FusionQuaternion<T>);
+    fn reset(&mut self);
+    fn flags(&self) -> FusionAhrsFlags;
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FusionAhrs<T: FusionFloat = f32> {
+    pub settings: FusionAhrsSettings<T>,
+    pub quaternion: FusionQuaternion<T>,
+    pub acc: FusionVector<T>,
     pub initialising: bool,
-    pub ramped_gain: f32,
-    pub ramped_gain_step: f32,
+    pub ramped_gain: T,
+    pub ramped_gain_step: T,
     pub angular_rate_recovery: bool,
-    pub half_accelerometer_feedback: FusionVector,
-    pub half_magnetometer_feedback: FusionVector,
+    pub half_accelerometer_feedback: FusionVector<T>,
+    pub half_magnetometer_feedback: FusionVector<T>,
     pub accelerometer_ignored: bool,
     pub acceleration_recovery_trigger: i32,
     pub acceleration_recovery_timeout: i32,
@@ -50,15 +239,43 @@ pub struct FusionAhrs {
     pub magnetic_recovery_timeout: i32,
 }
 
-pub struct FusionAhrsSettings {
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FusionAhrsSettings<T: FusionFloat = f32> {
     pub convention: FusionConvention,
-    pub gain: f32,
-    pub gyr_range: f32,
-    pub acc_rejection: f32,
-    pub mag_rejection: f32,
+    pub gain: T,
+    pub gyr_range: T,
+    pub acc_rejection: T,
+    pub mag_rejection: T,
     pub recovery_trigger_period: i32,
 }
 
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FusionError {
+    /* Gyroscope measurement is NaN or infinite */
+    NonFiniteGyroscope,
+    /* Accelerometer measurement is NaN or infinite */
+    NonFiniteAccelerometer,
+    /* Magnetometer measurement is NaN or infinite */
+    NonFiniteMagnetometer,
+    /* Heading is NaN or infinite */
+    NonFiniteHeading,
+    /* Delta time is NaN or infinite */
+    NonFiniteDeltaTime,
+    /* Timestamp is NaN or infinite */
+    NonFiniteTimestamp,
+    /* Quaternion became NaN or infinite during integration, the previous state was restored */
+    NonFiniteQuaternion,
+    /* Saved gyroscope offset has a different format version */
+    GyrOffsetVersionMismatch,
+    /* Saved gyroscope offset was learned at a different sample rate */
+    GyrOffsetSampleRateMismatch,
+    /* Saved state is corrupt, its checksum does not match or it contains NaN or infinite values */
+    InvalidSavedState,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FusionAhrsFlags {
     pub initializing: bool,
     pub angular_rate_recovery: bool,
@@ -66,83 +283,194 @@ pub struct FusionAhrsFlags {
     pub magnetic_recovery: bool,
 }
 
-#[derive(Copy, Clone)]
-pub struct Angle {
-    pub roll: f32,
-    pub pitch: f32,
-    pub yaw: f32,
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FusionAhrsInternalStates<T: FusionFloat = f32> {
+    pub acceleration_error: T,
+    pub accelerometer_ignored: bool,
+    pub acceleration_recovery_trigger: T,
+    pub magnetic_error: T,
+    pub magnetometer_ignored: bool,
+    pub magnetic_recovery_trigger: T,
+}
+
+/// Original gradient-descent Madgwick filter, kept to compare against legacy firmware using it.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MadgwickAhrs<T: FusionFloat = f32> {
+    pub settings: MadgwickAhrsSettings<T>,
+    pub quaternion: FusionQuaternion<T>,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MadgwickAhrsSettings<T: FusionFloat = f32> {
+    pub convention: FusionConvention,
+    /* Gradient descent step size in rad/s, the expected gyroscope error times sqrt(3/4) */
+    pub beta: T,
+}
+
+/// Mahony complementary filter with proportional and integral feedback, the integral estimating the gyroscope bias
+/// while moving, for platforms that are never stationary long enough for `FusionGyrOffset`.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MahonyAhrs<T: FusionFloat = f32> {
+    pub settings: MahonyAhrsSettings<T>,
+    pub quaternion: FusionQuaternion<T>,
+    /* Estimated gyroscope bias in degrees/s, subtracted from the gyroscope measurement */
+    pub gyr_bias: FusionVector<T>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MahonyAhrsSettings<T: FusionFloat = f32> {
+    pub convention: FusionConvention,
+    /* Proportional gain in rad/s per unit of error */
+    pub kp: T,
+    /* Integral gain in rad/s^2 per unit of error, zero disables bias estimation */
+    pub ki: T,
+    /* Anti-windup limit of each bias component in degrees/s, zero disables the limit */
+    pub bias_limit: T,
+    /* Bias corrected angular rate in degrees/s above which the bias is not integrated, zero disables the limit */
+    pub bias_rate_limit: T,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Angle<T: FusionFloat = f32> {
+    pub roll: T,
+    pub pitch: T,
+    pub yaw: T,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(dead_code)]
-pub struct FusionVector {
-    pub x: f32,
-    pub y: f32,
-    pub z: f32,
-}
-
-#[derive(Copy, Clone)]
-pub struct FusionMatrix {
-    pub xx: f32,
-    pub xy: f32,
-    pub xz: f32,
-    pub yx: f32,
-    pub yy: f32,
-    pub yz: f32,
-    pub zx: f32,
-    pub zy: f32,
-    pub zz: f32,
-}
-
-#[derive(Copy, Clone)]
-pub struct FusionQuaternion {
-    pub w: f32,
-    pub x: f32,
-    pub y: f32,
-    pub z: f32,
-}
-
-#[derive(Copy, Clone)]
+pub struct FusionVector<T: FusionFloat = f32> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FusionMatrix<T: FusionFloat = f32> {
+    pub xx: T,
+    pub xy: T,
+    pub xz: T,
+    pub yx: T,
+    pub yy: T,
+    pub yz: T,
+    pub zx: T,
+    pub zy: T,
+    pub zz: T,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FusionQuaternion<T: FusionFloat = f32> {
+    pub w: T,
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(dead_code)]
-pub struct FusionEuler {
-    pub angle: Angle,
+pub struct FusionEuler<T: FusionFloat = f32> {
+    pub angle: Angle<T>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FusionGyrOffset<T: FusionFloat = f32> {
+    pub settings: FusionGyrOffsetSettings<T>,
+    pub sample_rate: u32,
+    pub filter_coefficient: T,
+    pub timeout: u32,
+    pub timer: u32,
+    pub gyroscope_offset: FusionVector<T>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FusionGyrOffsetSettings<T: FusionFloat = f32> {
+    /* Threshold in degrees per second below which the gyroscope is considered stationary */
+    pub threshold: T,
+    /* Time in seconds the gyroscope must be stationary before the offset adapts */
+    pub timeout: T,
+    /* Cutoff frequency in Hz of the offset low-pass filter */
+    pub cutoff_frequency: T,
 }
 
-pub struct FusionGyrOffset {
-    pub filter_coefficient: f32,
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FusionGyrTempOffset<T: FusionFloat = f32> {
+    pub settings: FusionGyrTempOffsetSettings<T>,
+    pub sample_rate: u32,
+    pub filter_coefficient: T,
     pub timeout: u32,
     pub timer: u32,
-    pub gyroscope_offset: FusionVector,
+    /* Filtered offset of each equally spaced temperature bin from min_temperature to max_temperature */
+    pub bins: [FusionVector<T>; FUSION_GYR_TEMP_OFFSET_BINS],
+    /* Filtered temperature at which the offset of each bin was learned */
+    pub bin_temperatures: [T; FUSION_GYR_TEMP_OFFSET_BINS],
+    pub learned: [bool; FUSION_GYR_TEMP_OFFSET_BINS],
 }
 
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FusionGyrTempOffsetSettings<T: FusionFloat = f32> {
+    /* Stationary detection and filter parameters, same meaning as for FusionGyrOffset */
+    pub offset: FusionGyrOffsetSettings<T>,
+    /* Temperature of the first bin in degrees Celsius */
+    pub min_temperature: T,
+    /* Temperature of the last bin in degrees Celsius */
+    pub max_temperature: T,
+}
+
+// Number of temperature bins of the temperature-compensated gyroscope offset.
+pub const FUSION_GYR_TEMP_OFFSET_BINS: usize = 16;
+
+// Format version of the saved gyroscope offset state.
+pub const FUSION_GYR_OFFSET_STATE_VERSION: u8 = 1;
+
+// Size in bytes of the saved gyroscope offset state: version, sample rate, timer, offset x, y, z and a CRC-8 checksum.
+pub const FUSION_GYR_OFFSET_STATE_SIZE: usize = 22;
+
 // Timeout in seconds.
-const TIMEOUT: u32 = 5;
+const TIMEOUT: f64 = 5.0f64;
 
 // Cutoff frequency in Hz.
-const CUTOFF_FREQUENCY: f32 = 0.02f32;
+const CUTOFF_FREQUENCY: f64 = 0.02f64;
 
 // Threshold in degrees per second.
-const THRESHOLD: f32 = 3f32;
+const THRESHOLD: f64 = 3f64;
+
+// Nominal sample rate in Hz of a default gyroscope offset, the rate used throughout the examples.
+const DEFAULT_SAMPLE_RATE: u32 = 100;
 
-fn fusion_degrees_to_radians(degrees: f32) -> f32 {
-    degrees * (core::f32::consts::PI / 180.0f32)
+fn fusion_degrees_to_radians<T: FusionFloat>(degrees: T) -> T {
+    degrees * (T::PI / T::from_f64(180.0f64))
 }
 
-fn fusion_radians_to_degrees(radians: f32) -> f32 {
-    radians * (180.0f32 / core::f32::consts::PI)
+fn fusion_radians_to_degrees<T: FusionFloat>(radians: T) -> T {
+    radians * (T::from_f64(180.0f64) / T::PI)
 }
 
-fn asin_safe(value: f32) -> f32 {
-    use libm::{asinf};
-    if value <= -1.0f32 {
-        return core::f32::consts::PI / -2.0f32;
+fn asin_safe<T: FusionFloat>(value: T) -> T {
+    if value <= -T::ONE {
+        return T::PI / -T::TWO;
     }
-    if value >= 1.0f32 {
-        return core::f32::consts::PI / 2.0f32;
+    if value >= T::ONE {
+        return T::PI / T::TWO;
     }
-    asinf(value)
+    value.asin()
 }
 
+#[allow(clippy::excessive_precision)]
+#[cfg_attr(feature = "fusion-use-normal-sqrt", allow(dead_code))]
 fn fusion_fast_inverse_sqrt(x: f32) -> f32 {
     union Union32 {
         f: f32,
@@ -159,9 +487,8 @@ fn fusion_fast_inverse_sqrt(x: f32) -> f32 {
 
 #[test]
 fn fusion_fast_inverse_sqrt_test() {
-    use libm::{fabsf};
     let result = fusion_fast_inverse_sqrt(9.0f32);
     let actual = 1f32 / result;
     let expected = 3f32;
-    assert!(fabsf(actual - expected) < 0.01f32);
+    assert!((actual - expected).abs() < 0.01f32);
 }
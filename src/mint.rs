@@ -0,0 +1,86 @@
+//! Conversions between the Fusion math types and mint.
+//!
+//! `mint::Quaternion` has the vector part `v` and the scalar part `s`. `RowMatrix3` stores rows, `RowMatrix3::x` is
+//! `(m.xx, m.xy, m.xz)`, and `ColumnMatrix3` stores columns, `ColumnMatrix3::x` is `(m.xx, m.yx, m.zx)`.
+use crate::{FusionFloat, FusionMatrix, FusionQuaternion, FusionVector};
+use mint::{ColumnMatrix3, Quaternion, RowMatrix3, Vector3};
+
+impl<T: FusionFloat> From<Vector3<T>> for FusionVector<T> {
+    fn from(v: Vector3<T>) -> Self {
+        Self::new(v.x, v.y, v.z)
+    }
+}
+
+impl<T: FusionFloat> From<FusionVector<T>> for Vector3<T> {
+    fn from(v: FusionVector<T>) -> Self {
+        Vector3 { x: v.x, y: v.y, z: v.z }
+    }
+}
+
+impl<T: FusionFloat> From<Quaternion<T>> for FusionQuaternion<T> {
+    fn from(q: Quaternion<T>) -> Self {
+        Self {
+            w: q.s,
+            x: q.v.x,
+            y: q.v.y,
+            z: q.v.z,
+        }
+    }
+}
+
+impl<T: FusionFloat> From<FusionQuaternion<T>> for Quaternion<T> {
+    fn from(q: FusionQuaternion<T>) -> Self {
+        Quaternion { v: Vector3 { x: q.x, y: q.y, z: q.z }, s: q.w }
+    }
+}
+
+impl<T: FusionFloat> From<RowMatrix3<T>> for FusionMatrix<T> {
+    fn from(m: RowMatrix3<T>) -> Self {
+        Self::new(m.x.x, m.x.y, m.x.z, m.y.x, m.y.y, m.y.z, m.z.x, m.z.y, m.z.z)
+    }
+}
+
+impl<T: FusionFloat> From<FusionMatrix<T>> for RowMatrix3<T> {
+    fn from(m: FusionMatrix<T>) -> Self {
+        RowMatrix3 {
+            x: Vector3 { x: m.xx, y: m.xy, z: m.xz },
+            y: Vector3 { x: m.yx, y: m.yy, z: m.yz },
+            z: Vector3 { x: m.zx, y: m.zy, z: m.zz },
+        }
+    }
+}
+
+impl<T: FusionFloat> From<ColumnMatrix3<T>> for FusionMatrix<T> {
+    fn from(m: ColumnMatrix3<T>) -> Self {
+        Self::new(m.x.x, m.y.x, m.z.x, m.x.y, m.y.y, m.z.y, m.x.z, m.y.z, m.z.z)
+    }
+}
+
+impl<T: FusionFloat> From<FusionMatrix<T>> for ColumnMatrix3<T> {
+    fn from(m: FusionMatrix<T>) -> Self {
+        ColumnMatrix3 {
+            x: Vector3 { x: m.xx, y: m.yx, z: m.zx },
+            y: Vector3 { x: m.xy, y: m.yy, z: m.zy },
+            z: Vector3 { x: m.xz, y: m.yz, z: m.zz },
+        }
+    }
+}
+
+#[cfg(feature = "libm")]
+#[test]
+fn conversion_test() {
+    let q = FusionQuaternion::from_axis_angle(FusionVector::new(1.0f32, -2.0f32, 0.5f32), 0.8f32);
+    let v = FusionVector::new(0.3f32, 0.2f32, -1.0f32);
+    let m = FusionMatrix::from([[1.0f64, 2.0f64, 3.0f64], [4.0f64, 5.0f64, 6.0f64], [7.0f64, 8.0f64, 9.0f64]]);
+
+    // Exact round trips
+    assert_eq!(FusionQuaternion::from(Quaternion::from(q)), q);
+    assert_eq!(Quaternion::from(q).s, q.w);
+    assert_eq!(FusionVector::from(Vector3::from(v)), v);
+    assert_eq!(FusionMatrix::from(RowMatrix3::from(m)), m);
+    assert_eq!(FusionMatrix::from(ColumnMatrix3::from(m)), m);
+
+    // Row and column order
+    assert_eq!(RowMatrix3::from(m).x, Vector3 { x: 1.0f64, y: 2.0f64, z: 3.0f64 });
+    assert_eq!(ColumnMatrix3::from(m).x, Vector3 { x: 1.0f64, y: 4.0f64, z: 7.0f64 });
+}
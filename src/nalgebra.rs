@@ -1,12 +1,135 @@
-use crate::FusionVector;
-use nalgebra::{Vector3};
-
-impl Into<FusionVector> for Vector3<f32> {
-    fn into(self) -> FusionVector {
-        FusionVector {
-            x: self.x,
-            y: self.y,
-            z: self.z,
+//! Conversions between the Fusion math types and nalgebra.
+//!
+//! `FusionQuaternion` and nalgebra quaternions are both Hamilton quaternions with the scalar part `w`.
+//! The AHRS quaternion rotates vectors from the sensor frame to the earth frame, so
+//! `UnitQuaternion::from(fusion.quaternion()).transform_vector(&v)` expresses a sensor frame vector in the earth frame,
+//! and `Rotation3::from(fusion.quaternion())` is the same matrix as `FusionQuaternion::rotation`.
+//! `FusionMatrix` fields are named by row then column, e.g. `xy` is row 0, column 1 of `Matrix3`.
+use crate::{FusionFloat, FusionMatrix, FusionQuaternion, FusionVector};
+use nalgebra::{Matrix3, Point3, Quaternion, RealField, Rotation3, Scalar, UnitQuaternion, Vector3};
+
+impl<T: FusionFloat + Scalar> From<Vector3<T>> for FusionVector<T> {
+    fn from(v: Vector3<T>) -> Self {
+        Self {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+        }
+    }
+}
+
+impl<T: FusionFloat + Scalar> From<FusionVector<T>> for Vector3<T> {
+    fn from(v: FusionVector<T>) -> Self {
+        Vector3::new(v.x, v.y, v.z)
+    }
+}
+
+impl<T: FusionFloat + Scalar> From<Point3<T>> for FusionVector<T> {
+    fn from(p: Point3<T>) -> Self {
+        Self {
+            x: p.x,
+            y: p.y,
+            z: p.z,
         }
     }
 }
+
+impl<T: FusionFloat + Scalar> From<FusionVector<T>> for Point3<T> {
+    fn from(v: FusionVector<T>) -> Self {
+        Point3::new(v.x, v.y, v.z)
+    }
+}
+
+impl<T: FusionFloat + Scalar> From<Matrix3<T>> for FusionMatrix<T> {
+    fn from(m: Matrix3<T>) -> Self {
+        Self::new(m[(0, 0)], m[(0, 1)], m[(0, 2)], m[(1, 0)], m[(1, 1)], m[(1, 2)], m[(2, 0)], m[(2, 1)], m[(2, 2)])
+    }
+}
+
+impl<T: FusionFloat + Scalar> From<FusionMatrix<T>> for Matrix3<T> {
+    fn from(m: FusionMatrix<T>) -> Self {
+        Matrix3::new(m.xx, m.xy, m.xz, m.yx, m.yy, m.yz, m.zx, m.zy, m.zz)
+    }
+}
+
+impl<T: FusionFloat + Scalar> From<Quaternion<T>> for FusionQuaternion<T> {
+    fn from(q: Quaternion<T>) -> Self {
+        // nalgebra stores the coordinates as i, j, k, w
+        Self {
+            w: q.coords[3],
+            x: q.coords[0],
+            y: q.coords[1],
+            z: q.coords[2],
+        }
+    }
+}
+
+impl<T: FusionFloat + Scalar> From<FusionQuaternion<T>> for Quaternion<T> {
+    fn from(q: FusionQuaternion<T>) -> Self {
+        Quaternion::new(q.w, q.x, q.y, q.z)
+    }
+}
+
+impl<T: FusionFloat + RealField> From<UnitQuaternion<T>> for FusionQuaternion<T> {
+    fn from(q: UnitQuaternion<T>) -> Self {
+        q.into_inner().into()
+    }
+}
+
+impl<T: FusionFloat + RealField> From<FusionQuaternion<T>> for UnitQuaternion<T> {
+    /// Normalises the quaternion.
+    fn from(q: FusionQuaternion<T>) -> Self {
+        UnitQuaternion::from_quaternion(q.into())
+    }
+}
+
+impl<T: FusionFloat + RealField> From<Rotation3<T>> for FusionMatrix<T> {
+    fn from(r: Rotation3<T>) -> Self {
+        r.into_inner().into()
+    }
+}
+
+impl<T: FusionFloat + RealField> From<FusionMatrix<T>> for Rotation3<T> {
+    /// The matrix is assumed to be a rotation matrix and is not orthonormalised.
+    fn from(m: FusionMatrix<T>) -> Self {
+        Rotation3::from_matrix_unchecked(m.into())
+    }
+}
+
+impl<T: FusionFloat + RealField> From<Rotation3<T>> for FusionQuaternion<T> {
+    fn from(r: Rotation3<T>) -> Self {
+        FusionMatrix::from(r).into()
+    }
+}
+
+impl<T: FusionFloat + RealField> From<FusionQuaternion<T>> for Rotation3<T> {
+    fn from(q: FusionQuaternion<T>) -> Self {
+        q.rotation().into()
+    }
+}
+
+#[cfg(feature = "libm")]
+#[test]
+fn conversion_test() {
+    let q = FusionQuaternion::from_axis_angle(FusionVector::new(1.0f64, -2.0f64, 0.5f64), 0.8f64);
+    let v = FusionVector::new(0.3f64, 0.2f64, -1.0f64);
+
+    // Same rotation of vectors
+    let unit = UnitQuaternion::from(q);
+    let expected = q.rotate_vector(v);
+    let result = FusionVector::from(unit.transform_vector(&v.into()));
+    assert!(result.approx_eq(&expected, 1e-12f64));
+    assert!(FusionVector::from(unit.transform_point(&v.into())).approx_eq(&expected, 1e-12f64));
+    let rotation = Rotation3::from(q);
+    assert!(FusionMatrix::from(rotation).approx_eq(&q.rotation(), 1e-12f64));
+    assert!(FusionVector::from(rotation * Vector3::from(v)).approx_eq(&expected, 1e-12f64));
+
+    // Round trips
+    assert!(FusionQuaternion::from(unit).approx_eq(&q, 1e-12f64));
+    assert!(FusionQuaternion::from(rotation).angle_between(&q) < 1e-9f64);
+    assert_eq!(FusionQuaternion::from(Quaternion::from(q)), q);
+    let m = FusionMatrix::from([[1.0f64, 2.0f64, 3.0f64], [4.0f64, 5.0f64, 6.0f64], [7.0f64, 8.0f64, 9.0f64]]);
+    assert_eq!(Matrix3::from(m)[(0, 1)], 2.0f64);
+    assert_eq!(FusionMatrix::from(Matrix3::from(m)), m);
+    assert_eq!(FusionVector::from(Point3::from(v)), v);
+}
@@ -51,7 +51,7 @@ int main(void) {
     float t, last_t = 0, ax, ay, az, gx, gy, gz, mx, my, mz;
     // This loop should repeat each time new gyroscope data is available
 
-    fprintf(out, "dt,euler_yaw,euler_pitch,euler_roll,earth_x,earth_y,earth_z,q_w,q_x,q_y,q_z\n");
+    fprintf(out, "dt,euler_yaw,euler_pitch,euler_roll,earth_x,earth_y,earth_z,q_w,q_x,q_y,q_z,acc_error,acc_ignored,acc_recovery_trigger,mag_error,mag_ignored,mag_recovery_trigger\n");
     while (fscanf(in, "%f,%f,%f,%f,%f,%f,%f,%f,%f,%f\n", &t, &gx, &gy, &gz, &ax, &ay, &az, &mx, &my, &mz) != -1) {
         if (t == 0) {
             last_t = t;
@@ -79,10 +79,13 @@ int main(void) {
         
         const FusionEuler euler = FusionQuaternionToEuler(FusionAhrsGetQuaternion(&ahrs));
         const FusionVector earth = FusionAhrsGetEarthAcceleration(&ahrs);
+        const FusionAhrsInternalStates states = FusionAhrsGetInternalStates(&ahrs);
 
-        fprintf(out, "%.8f,%.8f,%.8f,%.8f,%.8f,%.8f,%.8f,%.8f,%.8f,%.8f,%.8f\n", t,
+        fprintf(out, "%.8f,%.8f,%.8f,%.8f,%.8f,%.8f,%.8f,%.8f,%.8f,%.8f,%.8f,%.8f,%d,%.8f,%.8f,%d,%.8f\n", t,
                euler.angle.yaw, euler.angle.pitch, euler.angle.roll,
-               earth.x, earth.y, earth.z, q.array[0], q.array[1], q.array[2], q.array[3]);
+               earth.x, earth.y, earth.z, q.array[0], q.array[1], q.array[2], q.array[3],
+               states.accelerationError, states.accelerometerIgnored, states.accelerationRecoveryTrigger,
+               states.magneticError, states.magnetometerIgnored, states.magneticRecoveryTrigger);
         printf("%.8f,%.8f,%.8f,%.8f,%.8f,%.8f,%.8f,%.8f,%.8f,%.8f,%.8f\n", t,
                euler.angle.yaw, euler.angle.pitch, euler.angle.roll,
                earth.x, earth.y, earth.z, q.array[0], q.array[1], q.array[2], q.array[3]);
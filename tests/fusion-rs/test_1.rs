@@ -3,6 +3,9 @@ mod tests {
     use imu_fusion::{Fusion, FusionAhrsSettings, FusionVector};
     use imu_fusion::FusionConvention::NWU;
 
+    // The C output is generated with the fast inverse square root
+    #[cfg_attr(feature = "fusion-use-normal-sqrt", ignore = "the C reference uses the fast inverse square root")]
+    #[cfg_attr(not(feature = "libm"), ignore = "micromath is less accurate than the C reference")]
     #[test]
     fn test1() {
         let mut reader = csv::Reader::from_path("tests/fusion_in.csv").unwrap();
@@ -14,7 +17,7 @@ mod tests {
         ahrs_settings.mag_rejection = 20.0f32;
 
         let mut fusion = Fusion::new(25, ahrs_settings);
-        writer.write_record(&["dt", "euler_yaw", "euler_pitch", "euler_roll", "earth_acc_x", "earth_acc_y", "earth_acc_z", "q_w", "q_x", "q_y", "q_z"]).unwrap();
+        writer.write_record(["dt", "euler_yaw", "euler_pitch", "euler_roll", "earth_x", "earth_y", "earth_z", "q_w", "q_x", "q_y", "q_z", "acc_error", "acc_ignored", "acc_recovery_trigger", "mag_error", "mag_ignored", "mag_recovery_trigger"]).unwrap();
         let (mut acc_x, mut acc_y, mut acc_z) = (0f32, 0f32, 0f32);
         for result in reader.deserialize() {
             let record: [f32; 10] = result.unwrap();
@@ -27,34 +30,71 @@ mod tests {
             let q = fusion.quaternion();
             let euler = fusion.euler();
             let earth_acc = fusion.earth_acc();
+            let states = fusion.internal_states();
             earth_acc.get(&mut acc_x, &mut acc_y, &mut acc_z);
-            writer.write_record(&[format!("{:.8}", dt), format!("{:.8}", euler.angle.yaw), format!("{:.8}", euler.angle.pitch), format!("{:.8}", euler.angle.roll), format!("{:.8}", acc_x), format!("{:.8}", acc_y), format!("{:.8}", acc_z), format!("{:.8}", q.w), format!("{:.8}", q.x), format!("{:.8}", q.y), format!("{:.8}", q.z)]).unwrap();
+            writer.write_record([format!("{:.8}", dt), format!("{:.8}", euler.angle.yaw), format!("{:.8}", euler.angle.pitch), format!("{:.8}", euler.angle.roll), format!("{:.8}", acc_x), format!("{:.8}", acc_y), format!("{:.8}", acc_z), format!("{:.8}", q.w), format!("{:.8}", q.x), format!("{:.8}", q.y), format!("{:.8}", q.z),
+                                  format!("{:.8}", states.acceleration_error), format!("{}", states.accelerometer_ignored as i32), format!("{:.8}", states.acceleration_recovery_trigger), format!("{:.8}", states.magnetic_error), format!("{}", states.magnetometer_ignored as i32), format!("{:.8}", states.magnetic_recovery_trigger)]).unwrap();
         }
         _ = writer.flush();
         compare_results();
     }
 
+    #[cfg(feature = "libm")]
+    #[test]
+    fn test_f64() {
+        let mut reader = csv::Reader::from_path("tests/fusion_in.csv").unwrap();
+        let mut reader_c = csv::Reader::from_path("tests/fusion_c_out.csv").unwrap();
+        let ahrs_settings = FusionAhrsSettings::<f64> {
+            convention: NWU,
+            gain: 0.5f64,
+            acc_rejection: 10.0f64,
+            mag_rejection: 20.0f64,
+            ..Default::default()
+        };
+
+        let mut fusion = Fusion::new(25, ahrs_settings);
+        for (result, result_c) in reader.deserialize().zip(reader_c.deserialize()) {
+            let record: [f64; 10] = result.unwrap();
+            let record_c: [f64; 17] = result_c.unwrap();
+            let gyr = FusionVector::new(record[1], record[2], record[3]);
+            let acc = FusionVector::new(record[4], record[5], record[6]);
+            let mag = FusionVector::new(record[7], record[8], record[9]);
+
+            fusion.update(gyr, acc, mag, record[0]);
+            let q = fusion.quaternion();
+            // Double precision results follow the single precision C implementation closely
+            for (actual, expected) in [q.w, q.x, q.y, q.z].iter().zip(&record_c[7..11]) {
+                assert!((actual - expected).abs() < 0.001f64);
+            }
+        }
+    }
+
     fn compare_results() {
         let mut reader_c = csv::Reader::from_path("tests/fusion_c_out.csv").unwrap();
         let mut reader_rs = csv::Reader::from_path("tests/fusion_rs_out.csv").unwrap();
-        loop {
-            let record_c = reader_c.deserialize::<[f32; 3]>().next();
-            let record_rs = reader_rs.deserialize::<[f32; 3]>().next();
-            match record_c {
-                Some(Ok(record_c)) => {
-                    match record_rs {
-                        Some(Ok(record_rs)) => {
-                            for i in 0..record_c.len() {
-                                assert!((record_c[i] - record_rs[i]).abs() < 0.0001f32);
-                            }
-                        }
-                        _ => panic!("Error reading [rs] record"),
-                    }
-                }
-                None => break,
-                _ => panic!("Error reading [c] record"),
+        // Compare columns present in both outputs, matched by header name
+        let headers_c = reader_c.headers().unwrap().clone();
+        let headers_rs = reader_rs.headers().unwrap().clone();
+        let columns: Vec<(usize, usize)> = headers_c.iter().enumerate()
+            .filter_map(|(i, name)| headers_rs.iter().position(|h| h == name).map(|j| (i, j)))
+            .collect();
+        // Every column of the C output, including the internal states, must be compared
+        for name in ["q_w", "q_x", "q_y", "q_z", "acc_error", "acc_ignored", "acc_recovery_trigger", "mag_error", "mag_ignored", "mag_recovery_trigger"] {
+            assert!(headers_c.iter().any(|h| h == name), "{} missing from [c] output", name);
+        }
+        assert_eq!(columns.len(), headers_c.len());
+        let mut records_rs = reader_rs.records();
+        for record_c in reader_c.records() {
+            let record_c = record_c.expect("Error reading [c] record");
+            let record_rs = match records_rs.next() {
+                Some(Ok(record_rs)) => record_rs,
+                _ => panic!("Error reading [rs] record"),
+            };
+            for &(i, j) in &columns {
+                let value_c: f32 = record_c[i].parse().unwrap();
+                let value_rs: f32 = record_rs[j].parse().unwrap();
+                assert!((value_c - value_rs).abs() < 0.0001f32, "{} differs: c {} rs {}", &headers_c[i], value_c, value_rs);
             }
         }
     }
 }
-
@@ -0,0 +1,98 @@
+// Reference output of the original gradient-descent Madgwick AHRS for tests/fusion-rs/madgwick.rs.
+// MadgwickAHRS.c and MadgwickAHRS.h are the implementation published by x-io Technologies, unmodified.
+// Build and run from this directory: cc -O0 -fno-strict-aliasing -o madgwick-c main.c -lm && ./madgwick-c
+#include <math.h>
+#include <stdio.h>
+
+// The fast inverse square root of the published filter reads a float as a long, which must be 32-bit
+#define long int
+#include "MadgwickAHRS.c"
+#undef long
+
+const char* in_filename = "../fusion_in.csv";
+const char* out_filename = "../madgwick_c_out.csv";
+
+typedef struct {
+    float w, x, y, z;
+} Quaternion;
+
+// The published filter is NWU only. ENU and NED are the NWU filter seen from the other earth frame,
+// the output is the NWU quaternion rotated by the orientation of NWU in that frame.
+#define NUMBER_OF_CONVENTIONS 3
+static const Quaternion frames[NUMBER_OF_CONVENTIONS] = {
+    {1.0f, 0.0f, 0.0f, 0.0f}, // NWU
+    {0.70710678f, 0.0f, 0.0f, 0.70710678f}, // ENU, 90 degrees about z
+    {0.0f, 1.0f, 0.0f, 0.0f}, // NED, 180 degrees about x
+};
+
+static Quaternion multiply(const Quaternion a, const Quaternion b) {
+    return (Quaternion) {
+        a.w * b.w - a.x * b.x - a.y * b.y - a.z * b.z,
+        a.w * b.x + a.x * b.w + a.y * b.z - a.z * b.y,
+        a.w * b.y - a.x * b.z + a.y * b.w + a.z * b.x,
+        a.w * b.z + a.x * b.y - a.y * b.x + a.z * b.w,
+    };
+}
+
+// The fast inverse square root of the published filter leaves the quaternion about 0.2% short of unit length
+static Quaternion normalise(const Quaternion q) {
+    const float norm = sqrtf(q.w * q.w + q.x * q.x + q.y * q.y + q.z * q.z);
+    return (Quaternion) {q.w / norm, q.x / norm, q.y / norm, q.z / norm};
+}
+
+static void load(const Quaternion q) {
+    q0 = q.w, q1 = q.x, q2 = q.y, q3 = q.z;
+}
+
+static Quaternion store(void) {
+    return (Quaternion) {q0, q1, q2, q3};
+}
+
+int main(void) {
+    FILE* in = fopen(in_filename, "r");
+    if (in == NULL) {
+        printf("Input file not found. %s", in_filename);
+        return -1;
+    }
+    FILE* out = fopen(out_filename, "w");
+    if (out == NULL) {
+        printf("Output file not found. %s", out_filename);
+        return -1;
+    }
+
+    const float degreesToRadians = 3.14159265358979323846f / 180.0f;
+    float t, ax, ay, az, gx, gy, gz, mx, my, mz;
+
+    fprintf(out, "t,imu_q_w,imu_q_x,imu_q_y,imu_q_z,q_w,q_x,q_y,q_z");
+    fprintf(out, ",enu_imu_q_w,enu_imu_q_x,enu_imu_q_y,enu_imu_q_z,enu_q_w,enu_q_x,enu_q_y,enu_q_z");
+    fprintf(out, ",ned_imu_q_w,ned_imu_q_x,ned_imu_q_y,ned_imu_q_z,ned_q_w,ned_q_x,ned_q_y,ned_q_z\n");
+    Quaternion imu = {1.0f, 0.0f, 0.0f, 0.0f}, marg = {1.0f, 0.0f, 0.0f, 0.0f};
+    while (fscanf(in, "%f,%f,%f,%f,%f,%f,%f,%f,%f,%f\n", &t, &gx, &gy, &gz, &ax, &ay, &az, &mx, &my, &mz) != -1) {
+        if (t == 0) {
+            continue;
+        }
+        // The published filter has a fixed sample rate of 512 Hz
+        gx *= degreesToRadians;
+        gy *= degreesToRadians;
+        gz *= degreesToRadians;
+
+        // 6-axis and 9-axis filters run side by side on the same input
+        load(imu);
+        MadgwickAHRSupdateIMU(gx, gy, gz, ax, ay, az);
+        imu = store();
+
+        load(marg);
+        MadgwickAHRSupdate(gx, gy, gz, ax, ay, az, mx, my, mz);
+        marg = store();
+
+        fprintf(out, "%.8f", t);
+        for (int c = 0; c < NUMBER_OF_CONVENTIONS; c++) {
+            const Quaternion i = normalise(multiply(frames[c], imu));
+            const Quaternion m = normalise(multiply(frames[c], marg));
+            fprintf(out, ",%.8f,%.8f,%.8f,%.8f,%.8f,%.8f,%.8f,%.8f", i.w, i.x, i.y, i.z, m.w, m.x, m.y, m.z);
+        }
+        fprintf(out, "\n");
+    }
+    fclose(out);
+    return 0;
+}